@@ -0,0 +1,41 @@
+use combine::Parser;
+use japanese_parser_test::parser::{japanese, parenthetical};
+use japanese_parser_test::{
+    align_typing_target, parse_typing_target, romaji_to_kana, Matcher, Script,
+};
+
+fn main() {
+    let result = parenthetical().parse("京(とかんだと)").map(|x| x.0);
+    println!("{:?}", result);
+
+    let result = japanese().parse("おちゃをのむ").map(|x| x.0);
+    println!("{:?}", result);
+
+    match parse_typing_target("本(ほん)屋(や)で切手(きって)をかう") {
+        Ok(target) => println!("{:?}", target),
+        Err(error) => println!("{}", error),
+    }
+
+    println!("{:?}", align_typing_target("お茶", "おちゃ"));
+
+    if let Ok(target) =
+        parse_typing_target("|新幹線新聞新人新入(しんかんせんしんぶんしんじんしんにゅう)")
+    {
+        println!("{:?}", target.hint(0));
+    }
+
+    if let Ok((target, _)) = japanese().parse("しゅくだい") {
+        let mut matcher = Matcher::new(target);
+        for c in "syukudaoi".chars() {
+            println!("{} {:?}", c, matcher.push(c));
+        }
+    }
+
+    for romaji in ["konnnichiha", "konna", "Fairu"] {
+        println!(
+            "{:?} {:?}",
+            romaji_to_kana(romaji, Script::Hiragana),
+            romaji_to_kana(romaji, Script::Katakana)
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use japanese_parser_test::dictionary::Dictionary;
+
+fn main() {
+    let dictionary = Dictionary::from_edict(
+        "食べる [たべる] /(v1,vt) to eat/\n\
+         寿司;鮨 [すし] /(n) sushi/\n\
+         今日 [きょう;こんにち] /(n) today/\n",
+    );
+
+    for text in &[
+        "寿司を食べる",
+        "鮨(すし)を食べる",
+        "寿司を食べた",
+        "今日",
+        "明日",
+    ] {
+        match dictionary.annotate(text) {
+            Ok(annotated) => println!("{} -> {}", text, annotated),
+            Err(e) => println!("{} -> {}", text, e),
+        }
+    }
+
+    println!("{:?}", dictionary.typing_target("寿司を食べる"));
+}
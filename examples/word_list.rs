@@ -0,0 +1,42 @@
+use japanese_parser_test::{
+    parse_typing_target_with_options, parse_word_list, ParseOptions, TypingTarget, WordListFormat,
+};
+
+fn main() {
+    let toml = r#"
+[[words]]
+text = "山(やま)のぼり"
+fixed = true
+weight = 2.0
+tags = ["sports", "outdoors"]
+
+[[words]]
+text = "ねこ"
+"#;
+    println!(
+        "{:?}",
+        parse_word_list(toml, WordListFormat::Toml, &ParseOptions::default())
+    );
+
+    let json = r#"{"words": [{"text": "いぬ", "disabled": true}, {"text": "猫"}]}"#;
+    match parse_word_list(json, WordListFormat::Json, &ParseOptions::default()) {
+        Ok(targets) => println!("{:?}", targets),
+        Err(error) => println!("{}", error),
+    }
+
+    let options = ParseOptions {
+        split_readings: true,
+        ..Default::default()
+    };
+    if let Ok(target) = parse_typing_target_with_options("山(やま)のぼり", &options) {
+        let json = serde_json::to_string(&target).unwrap_or_default();
+        println!("{}", json);
+        println!("{:?}", serde_json::from_str::<TypingTarget>(&json));
+    }
+    println!(
+        "{:?}",
+        serde_json::from_str::<TypingTarget>(
+            r#"{"displayed_chunks": ["ね"], "typed_chunks": [[{"kana": "ね", "typed": ["ne"]}]]}"#
+        )
+    );
+}
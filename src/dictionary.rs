@@ -0,0 +1,361 @@
+//! Readings for bare kanji, looked up in a local dictionary file.
+//!
+//! Only available with the `dictionary` feature.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::kana::{is_digit, is_kana, is_ruby_base, to_hiragana};
+use crate::{align_reading, parse_typing_target, Error, TypingTarget};
+
+/// The dictionary file formats that can be loaded. Files must be UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DictionaryFormat {
+    /// The JMdict XML file.
+    JMdict,
+    /// EDICT or EDICT2 lines, like `漢字 [かんじ] /(n) kanji/`.
+    Edict,
+    /// MeCab IPADIC CSV lines, with the reading in the twelfth field.
+    Ipadic,
+}
+
+/// Readings of words, keyed by how they are written.
+#[derive(Clone, Debug, Default)]
+pub struct Dictionary {
+    readings: HashMap<String, Vec<String>>,
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: impl AsRef<Path>, format: DictionaryFormat) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+
+        Ok(match format {
+            DictionaryFormat::JMdict => Self::from_jmdict(&text),
+            DictionaryFormat::Edict => Self::from_edict(&text),
+            DictionaryFormat::Ipadic => Self::from_ipadic(&text),
+        })
+    }
+
+    pub fn from_jmdict(text: &str) -> Self {
+        let mut dictionary = Self::new();
+
+        let mut kanji: Vec<String> = vec![];
+        // each reading, with the kanji it is restricted to, if any
+        let mut readings: Vec<(String, Vec<String>, bool)> = vec![];
+
+        for line in text.lines().map(str::trim) {
+            if line == "<entry>" {
+                kanji.clear();
+                readings.clear();
+            } else if let Some(keb) = element(line, "keb") {
+                kanji.push(keb.to_owned());
+            } else if let Some(reb) = element(line, "reb") {
+                readings.push((reb.to_owned(), vec![], false));
+            } else if let Some(restriction) = element(line, "re_restr") {
+                if let Some((_, restrictions, _)) = readings.last_mut() {
+                    restrictions.push(restriction.to_owned());
+                }
+            } else if line == "<re_nokanji/>" {
+                if let Some((_, _, no_kanji)) = readings.last_mut() {
+                    *no_kanji = true;
+                }
+            } else if line == "</entry>" {
+                for (reading, restrictions, no_kanji) in &readings {
+                    for surface in &kanji {
+                        if !no_kanji && (restrictions.is_empty() || restrictions.contains(surface))
+                        {
+                            dictionary.insert(surface, reading);
+                        }
+                    }
+                }
+            }
+        }
+
+        dictionary
+    }
+
+    pub fn from_edict(text: &str) -> Self {
+        let mut dictionary = Self::new();
+
+        for line in text.lines() {
+            // words written only in kana have no brackets, and need no reading
+            let (kanji, readings) = match line
+                .split_once(" /")
+                .and_then(|(head, _)| head.split_once(" ["))
+            {
+                Some((kanji, readings)) => (kanji, readings.trim_end_matches(']')),
+                None => continue,
+            };
+
+            let kanji: Vec<&str> = split_entries(kanji).map(strip_tags).collect();
+
+            for reading in split_entries(readings) {
+                // EDICT2 restricts readings to some of the kanji with
+                // `かな(漢字;感字)`, which looks just like the `(P)` tags.
+                let (reading, restrictions) = match reading.split_once('(') {
+                    Some((reading, rest)) if !rest.is_ascii() => (
+                        reading,
+                        rest.split(')').next().unwrap_or("").split(';').collect(),
+                    ),
+                    _ => (strip_tags(reading), vec![]),
+                };
+
+                for surface in &kanji {
+                    if restrictions.is_empty() || restrictions.contains(surface) {
+                        dictionary.insert(surface, reading);
+                    }
+                }
+            }
+        }
+
+        dictionary
+    }
+
+    pub fn from_ipadic(text: &str) -> Self {
+        let mut dictionary = Self::new();
+
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split(',').collect();
+
+            if let (Some(surface), Some(reading)) = (fields.first(), fields.get(11)) {
+                if *reading != "*" {
+                    dictionary.insert(surface, reading);
+                }
+            }
+        }
+
+        dictionary
+    }
+
+    /// Adds a reading for `surface`. Readings in katakana are stored as
+    /// hiragana, so the same reading in both scripts is only kept once.
+    pub fn insert(&mut self, surface: &str, reading: &str) {
+        let reading: String = reading.chars().map(to_hiragana).collect();
+        let readings = self.readings.entry(surface.to_owned()).or_default();
+
+        if !surface.is_empty() && !readings.contains(&reading) {
+            readings.push(reading);
+        }
+    }
+
+    /// Every known reading of `surface`.
+    pub fn readings(&self, surface: &str) -> &[String] {
+        self.readings.get(surface).map_or(&[], |r| r.as_slice())
+    }
+
+    /// Gives every run of kanji in `text` its reading, in the form read by
+    /// [`parse_typing_target`].
+    ///
+    /// The whole of `text` is looked up first. Otherwise each run of kanji is
+    /// looked up together with as much of the kana after it as possible, so
+    /// words written with kana endings like 食べる are found whole. Words are
+    /// not deinflected, so a conjugated form like 食べた is only found if the
+    /// dictionary has it. Kanji that already have a reading in parentheses are
+    /// left alone. Digits are read with the kanji after them if the dictionary
+    /// has them together, like 1日, and typed as they are otherwise.
+    pub fn annotate(&self, text: &str) -> Result<String, Error> {
+        if !self.readings(text).is_empty() {
+            return self.align(text);
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let in_run = |c: char| is_ruby_base(c) || is_digit(c);
+        let mut annotated = String::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            if !in_run(chars[i]) {
+                annotated.push(chars[i]);
+                i += 1;
+                continue;
+            }
+
+            let run_end = (i..chars.len())
+                .find(|&j| !in_run(chars[j]))
+                .unwrap_or(chars.len());
+
+            if let Some(close) = chars.get(run_end).and_then(|c| closing_bracket(*c)) {
+                let end = (run_end..chars.len())
+                    .find(|&j| chars[j] == close)
+                    .map_or(chars.len(), |j| j + 1);
+                annotated.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+
+            let kana_end = (run_end..chars.len())
+                .find(|&j| !is_kana(chars[j]))
+                .unwrap_or(chars.len());
+
+            let surface = match (run_end..=kana_end)
+                .rev()
+                .map(|end| chars[i..end].iter().collect::<String>())
+                .find(|surface| !self.readings(surface).is_empty())
+            {
+                Some(surface) => surface,
+                // digits can be typed as they are, apart from any kanji after
+                // them, like the 3 of 3月
+                None if is_digit(chars[i]) => {
+                    let digits_end = (i..run_end)
+                        .find(|&j| !is_digit(chars[j]))
+                        .unwrap_or(run_end);
+                    annotated.extend(&chars[i..digits_end]);
+                    i = digits_end;
+                    continue;
+                }
+                None => {
+                    return Err(Error::UnknownWord {
+                        word: chars[i..run_end].iter().collect(),
+                    })
+                }
+            };
+
+            annotated.push_str(&self.align(&surface)?);
+            i += surface.chars().count();
+        }
+
+        Ok(annotated)
+    }
+
+    /// Like [`annotate`](Dictionary::annotate), parsed straight into a target.
+    pub fn typing_target(&self, text: &str) -> Result<TypingTarget, Error> {
+        parse_typing_target(&self.annotate(text)?)
+    }
+
+    fn align(&self, surface: &str) -> Result<String, Error> {
+        match self.readings(surface) {
+            [reading] => align_reading(surface, reading),
+            readings => Err(Error::AmbiguousWord {
+                word: surface.to_owned(),
+                readings: readings.to_vec(),
+            }),
+        }
+    }
+}
+
+fn closing_bracket(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '（' => Some('）'),
+        _ => None,
+    }
+}
+
+/// The text of `<name>text</name>`, if that is all the line is.
+fn element<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    line.strip_prefix('<')?
+        .strip_prefix(name)?
+        .strip_prefix('>')?
+        .strip_suffix('>')?
+        .strip_suffix(name)?
+        .strip_suffix("</")
+}
+
+/// Splits `;`-separated EDICT entries, but not inside parentheses.
+fn split_entries(entries: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+
+    entries
+        .split(move |c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            c == ';' && depth == 0
+        })
+        .filter(|entry| !entry.is_empty())
+}
+
+/// Removes EDICT tags like the `(P)` in `漢字(P)`.
+fn strip_tags(entry: &str) -> &str {
+    entry.split('(').next().unwrap_or(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edict() -> Dictionary {
+        Dictionary::from_edict(
+            "食べる [たべる] /(v1,vt) to eat/\n\
+             寿司(P);鮨 [すし] /(n) sushi/\n\
+             今日 [きょう;こんにち] /(n) today/\n\
+             月 [がつ] /(n) month/\n\
+             1日 [ついたち] /(n) first day of the month/\n",
+        )
+    }
+
+    #[test]
+    fn loads_each_format() {
+        let jmdict = Dictionary::from_jmdict(
+            "<entry>\n<keb>今日</keb>\n<keb>今</keb>\n<reb>きょう</reb>\n<re_restr>今日</re_restr>\n\
+             <reb>こんにち</reb>\n<re_restr>今日</re_restr>\n<reb>コンニチ</reb>\n<re_nokanji/>\n</entry>\n\
+             <entry>\n<keb>猫</keb>\n<reb>ねこ</reb>\n</entry>\n",
+        );
+        assert_eq!(jmdict.readings("今日"), ["きょう", "こんにち"]);
+        assert!(jmdict.readings("今").is_empty());
+        assert_eq!(jmdict.readings("猫"), ["ねこ"]);
+
+        let edict = Dictionary::from_edict("日本;日本國 [にほん(日本);にっぽん] /(n) Japan/\n");
+        assert_eq!(edict.readings("日本"), ["にほん", "にっぽん"]);
+        assert_eq!(edict.readings("日本國"), ["にっぽん"]);
+
+        let ipadic = Dictionary::from_ipadic(
+            "食べる,1,1,1,動詞,自立,*,*,一段,基本形,食べる,タベル,タベル\n\
+             ＊,1,1,1,記号,一般,*,*,*,*,＊,*,*\n",
+        );
+        assert_eq!(ipadic.readings("食べる"), ["たべる"]);
+        assert!(ipadic.readings("＊").is_empty());
+    }
+
+    #[test]
+    fn annotates_each_word() {
+        let dictionary = edict();
+
+        assert_eq!(
+            dictionary.annotate("寿司を食べる").unwrap(),
+            "寿司(すし)を食(た)べる"
+        );
+        assert_eq!(
+            dictionary.annotate("鮨(すし)を食べる").unwrap(),
+            "鮨(すし)を食(た)べる"
+        );
+        assert!(dictionary.typing_target("寿司を食べる").is_ok());
+    }
+
+    #[test]
+    fn reports_missing_and_ambiguous_words() {
+        let dictionary = edict();
+
+        assert_eq!(
+            dictionary.annotate("寿司を食べた"),
+            Err(Error::UnknownWord {
+                word: "食".to_owned()
+            })
+        );
+        assert_eq!(
+            dictionary.annotate("今日"),
+            Err(Error::AmbiguousWord {
+                word: "今日".to_owned(),
+                readings: vec!["きょう".to_owned(), "こんにち".to_owned()],
+            })
+        );
+    }
+
+    #[test]
+    fn reads_digits_apart_from_kanji_unless_they_are_a_word() {
+        let dictionary = edict();
+
+        assert_eq!(dictionary.annotate("3月").unwrap(), "3月(がつ)");
+        assert_eq!(dictionary.annotate("1日").unwrap(), "|1日(ついたち)");
+        assert_eq!(dictionary.annotate("12").unwrap(), "12");
+        assert!(dictionary.typing_target("3月").is_ok());
+    }
+}
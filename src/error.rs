@@ -0,0 +1,232 @@
+use combine::stream::easy;
+use std::fmt;
+
+/// Why a line could not be parsed into a [`TypingTarget`](crate::TypingTarget).
+///
+/// Offsets point at the start of the offending characters, counted in chars
+/// and in bytes from the start of the line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A kana or combination of kana that has no known romaji spelling.
+    UnknownKana {
+        kana: String,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// A `<ruby>` element with a different number of bases and readings.
+    UnpairedRuby {
+        bases: usize,
+        readings: usize,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// Kanji that are not followed by a reading.
+    MissingReading {
+        kanji: String,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// A reading closed with a different bracket than it was opened with.
+    MismatchedBracket {
+        expected: char,
+        found: char,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// A character that is not allowed here, or `None` if the line ended early.
+    Unexpected {
+        found: Option<char>,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// A line with nothing to type, like one of only spaces.
+    NothingToType,
+    /// A reading that can't be lined up with the kana in its word.
+    ReadingMismatch { surface: String, reading: String },
+    /// A reading that lines up with the kana in its word in more than one way.
+    AmbiguousReading { surface: String, reading: String },
+    /// Kanji that have no reading in the dictionary.
+    UnknownWord { word: String },
+    /// A word with more than one reading in the dictionary.
+    AmbiguousWord { word: String, readings: Vec<String> },
+    /// An attribute of a word-list entry that is not known, or has a bad
+    /// value.
+    InvalidAttribute { attribute: String },
+    /// Romaji that no kana is spelled with.
+    UnknownRomaji {
+        romaji: String,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+    /// Romaji that may be read as any of `kana`, like the "nna" of "konna".
+    AmbiguousRomaji {
+        romaji: String,
+        kana: Vec<String>,
+        char_offset: usize,
+        byte_offset: usize,
+    },
+}
+
+impl Error {
+    pub(crate) fn from_parse_errors(line: &str, errors: easy::Errors<char, &str, usize>) -> Self {
+        let char_offset = errors.position;
+        let byte_offset = line
+            .char_indices()
+            .nth(char_offset)
+            .map_or(line.len(), |(i, _)| i);
+
+        for error in &errors.errors {
+            if let easy::Error::Other(other) = error {
+                if let Some(failure) = other.downcast_ref::<Failure>() {
+                    return failure.at(char_offset, byte_offset);
+                }
+            }
+        }
+
+        Error::Unexpected {
+            found: line[byte_offset..].chars().next(),
+            char_offset,
+            byte_offset,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownKana {
+                kana, char_offset, ..
+            } => write!(f, "unknown kana `{}` at character {}", kana, char_offset),
+            Error::UnpairedRuby {
+                bases,
+                readings,
+                char_offset,
+                ..
+            } => write!(
+                f,
+                "ruby with {} bases and {} readings at character {}",
+                bases, readings, char_offset
+            ),
+            Error::MissingReading {
+                kanji, char_offset, ..
+            } => write!(f, "no reading for `{}` at character {}", kanji, char_offset),
+            Error::MismatchedBracket {
+                expected,
+                found,
+                char_offset,
+                ..
+            } => write!(
+                f,
+                "expected `{}` but found `{}` at character {}",
+                expected, found, char_offset
+            ),
+            Error::Unexpected {
+                found: Some(c),
+                char_offset,
+                ..
+            } => write!(f, "unexpected `{}` at character {}", c, char_offset),
+            Error::Unexpected {
+                found: None,
+                char_offset,
+                ..
+            } => write!(f, "unexpected end of line at character {}", char_offset),
+            Error::NothingToType => write!(f, "nothing to type"),
+            Error::ReadingMismatch { surface, reading } => {
+                write!(f, "`{}` can't be read as `{}`", surface, reading)
+            }
+            Error::AmbiguousReading { surface, reading } => write!(
+                f,
+                "`{}` can be read as `{}` in more than one way",
+                surface, reading
+            ),
+            Error::UnknownWord { word } => write!(f, "no reading for `{}`", word),
+            Error::AmbiguousWord { word, readings } => write!(
+                f,
+                "`{}` can be read as any of `{}`",
+                word,
+                readings.join("`, `")
+            ),
+            Error::InvalidAttribute { attribute } => {
+                write!(f, "invalid attribute `{}`", attribute)
+            }
+            Error::UnknownRomaji {
+                romaji,
+                char_offset,
+                ..
+            } => write!(f, "no kana for `{}` at character {}", romaji, char_offset),
+            Error::AmbiguousRomaji {
+                romaji,
+                kana,
+                char_offset,
+                ..
+            } => write!(
+                f,
+                "`{}` at character {} can be any of `{}`",
+                romaji,
+                char_offset,
+                kana.join("`, `")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Raised from inside the parsers and turned into an [`Error`] once the
+/// position is known.
+#[derive(Debug)]
+pub(crate) enum Failure {
+    UnknownKana(String),
+    UnpairedRuby { bases: usize, readings: usize },
+    MissingReading(String),
+    MismatchedBracket { expected: char, found: char },
+    NothingToType,
+}
+
+impl Failure {
+    fn at(&self, char_offset: usize, byte_offset: usize) -> Error {
+        match self {
+            Failure::UnknownKana(kana) => Error::UnknownKana {
+                kana: kana.clone(),
+                char_offset,
+                byte_offset,
+            },
+            Failure::UnpairedRuby { bases, readings } => Error::UnpairedRuby {
+                bases: *bases,
+                readings: *readings,
+                char_offset,
+                byte_offset,
+            },
+            Failure::MissingReading(kanji) => Error::MissingReading {
+                kanji: kanji.clone(),
+                char_offset,
+                byte_offset,
+            },
+            Failure::MismatchedBracket { expected, found } => Error::MismatchedBracket {
+                expected: *expected,
+                found: *found,
+                char_offset,
+                byte_offset,
+            },
+            Failure::NothingToType => Error::NothingToType,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::UnknownKana(kana) => write!(f, "unknown kana `{}`", kana),
+            Failure::UnpairedRuby { bases, readings } => {
+                write!(f, "ruby with {} bases and {} readings", bases, readings)
+            }
+            Failure::MissingReading(kanji) => write!(f, "no reading for `{}`", kanji),
+            Failure::MismatchedBracket { expected, found } => {
+                write!(f, "expected `{}` but found `{}`", expected, found)
+            }
+            Failure::NothingToType => write!(f, "nothing to type"),
+        }
+    }
+}
+
+impl std::error::Error for Failure {}
@@ -0,0 +1,192 @@
+//! Lining up words with their readings, as they come from dictionaries.
+
+use crate::kana::{is_digit, is_ruby_base, to_hiragana};
+use crate::{parse_typing_target, Error, TypingTarget};
+
+enum Segment {
+    /// Kana, punctuation, Latin letters and digits, which the reading must
+    /// repeat.
+    Literal(Vec<char>),
+    Base(String),
+}
+
+/// Gives each run of kanji in `surface` its part of `reading`, in the form
+/// read by [`parse_typing_target`].
+///
+/// Everything between the kanji, like kana, punctuation and Latin letters,
+/// must match the reading, so `("食べる", "たべる")` becomes `食(た)べる` and
+/// `("はい、そう", "はい、そう")` stays as it is. Katakana and hiragana match
+/// each other. Digits are matched too, unless they are read together with a
+/// kanji, as in `|1日(ついたち)`.
+pub fn align_reading(surface: &str, reading: &str) -> Result<String, Error> {
+    let mut segments: Vec<Segment> = vec![];
+
+    for c in surface.chars() {
+        let base = is_ruby_base(c) || is_digit(c);
+
+        match segments.last_mut() {
+            Some(Segment::Base(text)) if base => text.push(c),
+            Some(Segment::Literal(literal)) if !base => literal.push(c),
+            _ if base => segments.push(Segment::Base(c.into())),
+            _ => segments.push(Segment::Literal(vec![c])),
+        }
+    }
+
+    for segment in &mut segments {
+        if let Segment::Base(base) = segment {
+            if base.chars().all(is_digit) {
+                *segment = Segment::Literal(base.chars().collect());
+            }
+        }
+    }
+
+    let reading: Vec<char> = reading.chars().collect();
+
+    let mut alignments = vec![];
+    align(&segments, &reading, &mut vec![], &mut alignments);
+
+    if alignments.len() != 1 {
+        let surface = surface.to_owned();
+        let reading = reading.iter().collect();
+
+        return Err(if alignments.is_empty() {
+            Error::ReadingMismatch { surface, reading }
+        } else {
+            Error::AmbiguousReading { surface, reading }
+        });
+    }
+
+    let mut aligned = String::new();
+    let mut readings = alignments.remove(0).into_iter();
+
+    for segment in &segments {
+        match segment {
+            Segment::Literal(literal) => aligned.extend(literal),
+            Segment::Base(base) => {
+                // digits are only read together with kanji after a marker
+                if base.chars().any(is_digit) {
+                    aligned.push('|');
+                }
+                aligned.push_str(base);
+                aligned.push('(');
+                aligned.extend(readings.next().unwrap_or_default());
+                aligned.push(')');
+            }
+        }
+    }
+
+    Ok(aligned)
+}
+
+/// Like [`align_reading`], parsed straight into a target.
+pub fn align_typing_target(surface: &str, reading: &str) -> Result<TypingTarget, Error> {
+    parse_typing_target(&align_reading(surface, reading)?)
+}
+
+/// Collects up to two ways of splitting `reading` between the bases, which is
+/// enough to tell whether there is exactly one.
+fn align<'a>(
+    segments: &[Segment],
+    reading: &'a [char],
+    current: &mut Vec<&'a [char]>,
+    alignments: &mut Vec<Vec<&'a [char]>>,
+) {
+    if alignments.len() > 1 {
+        return;
+    }
+
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            if reading.is_empty() {
+                alignments.push(current.clone());
+            }
+            return;
+        }
+    };
+
+    match segment {
+        Segment::Literal(literal) => {
+            let matches = literal.len() <= reading.len()
+                && literal
+                    .iter()
+                    .zip(reading)
+                    .all(|(k, r)| to_hiragana(*k) == to_hiragana(*r));
+
+            if matches {
+                align(rest, &reading[literal.len()..], current, alignments);
+            }
+        }
+        Segment::Base(_) => {
+            for len in 1..=reading.len() {
+                current.push(&reading[..len]);
+                align(rest, &reading[len..], current, alignments);
+                current.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_each_run_of_kanji_its_reading() {
+        assert_eq!(align_reading("食べる", "たべる").unwrap(), "食(た)べる");
+        assert_eq!(
+            align_reading("持ち帰り", "もちかえり").unwrap(),
+            "持(も)ち帰(かえ)り"
+        );
+        assert_eq!(align_reading("1日", "ついたち").unwrap(), "|1日(ついたち)");
+        assert_eq!(
+            align_reading("一ヶ月", "いっかげつ").unwrap(),
+            "一ヶ月(いっかげつ)"
+        );
+    }
+
+    #[test]
+    fn matches_katakana_with_hiragana() {
+        assert_eq!(
+            align_reading("山ノ内", "ヤマノウチ").unwrap(),
+            "山(ヤマ)ノ内(ウチ)"
+        );
+    }
+
+    #[test]
+    fn reports_readings_that_do_not_fit() {
+        assert_eq!(
+            align_reading("食べる", "のむ"),
+            Err(Error::ReadingMismatch {
+                surface: "食べる".to_owned(),
+                reading: "のむ".to_owned(),
+            })
+        );
+        assert_eq!(
+            align_reading("上の下", "うえのしたのした"),
+            Err(Error::AmbiguousReading {
+                surface: "上の下".to_owned(),
+                reading: "うえのしたのした".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn matches_punctuation_and_latin_literally() {
+        assert_eq!(
+            align_reading("はい、そう", "はい、そう").unwrap(),
+            "はい、そう"
+        );
+        assert_eq!(
+            align_reading("CDを買う", "CDをかう").unwrap(),
+            "CDを買(か)う"
+        );
+        assert_eq!(
+            align_reading("はい、そう", "はいそう"),
+            Err(Error::ReadingMismatch {
+                surface: "はい、そう".to_owned(),
+                reading: "はいそう".to_owned(),
+            })
+        );
+    }
+}
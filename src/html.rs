@@ -0,0 +1,203 @@
+//! Reading HTML `<ruby>` markup, like `<ruby>漢字<rt>かんじ</rt></ruby>`, with
+//! the same chunks as [`parenthetical`](crate::parser::parenthetical).
+
+use combine::error::StreamError;
+use combine::parser::char::string_cmp;
+use combine::stream::StreamErrorFor;
+use combine::{
+    attempt, choice, look_ahead, many, many1, satisfy, skip_many, token, ParseError, Parser, Stream,
+};
+
+use crate::error::Failure;
+use crate::parser::{
+    kana_chunk, latin_chunk, missing_reading, punctuation_chunk, space_chunk, typing_target,
+    DisplayedTypedPair,
+};
+use crate::{ParseOptions, TypingTarget};
+
+enum RubyPart {
+    Base(String),
+    Reading(Vec<DisplayedTypedPair>),
+    /// `<rp>` fallbacks, and whitespace between the tags.
+    Skipped,
+}
+
+/// A line of kana, punctuation, Latin letters and digits, spaces, and `<ruby>`
+/// elements.
+pub fn html<Input>() -> impl Parser<Input, Output = TypingTarget>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    html_with_options(ParseOptions::default())
+}
+
+/// Like [`html`], with settings other than the defaults.
+pub fn html_with_options<Input>(options: ParseOptions) -> impl Parser<Input, Output = TypingTarget>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    many1::<Vec<Vec<DisplayedTypedPair>>, _, _>(choice((
+        kana_chunk().map(|pair| vec![pair]),
+        punctuation_chunk().map(|pair| vec![pair]),
+        ruby_element(),
+        latin_chunk(options.case_sensitive, options.require_ascii_digits).map(|pair| vec![pair]),
+        space_chunk(options.spaces).map(|pair| vec![pair]),
+        missing_reading().map(|pair| vec![pair]),
+    )))
+    .and_then(move |pairs| -> Result<_, StreamErrorFor<Input>> {
+        typing_target(pairs.into_iter().flatten().collect(), &options)
+            .map_err(StreamErrorFor::<Input>::other)
+    })
+}
+
+/// A `<ruby>` element, with one pair for each base and reading.
+///
+/// Bases and readings may alternate, as in
+/// `<ruby>漢<rt>かん</rt>字<rt>じ</rt></ruby>`, or come as all of the `<rb>`s
+/// followed by all of the `<rt>`s. `<rp>` fallbacks are skipped, and so is
+/// whitespace around the bases, so the markup may be pretty-printed.
+pub fn ruby_element<Input>() -> impl Parser<Input, Output = Vec<DisplayedTypedPair>>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let text = || {
+        many1(satisfy(|c| c != '<')).map(|text: String| match text.trim() {
+            "" => RubyPart::Skipped,
+            base => RubyPart::Base(base.to_owned()),
+        })
+    };
+
+    attempt(open_tag("ruby"))
+        .with(many::<Vec<RubyPart>, _, _>(choice((
+            attempt(open_tag("rp"))
+                .with(skip_many(satisfy(|c| c != '<')))
+                .skip(close_tag("rp"))
+                .map(|_| RubyPart::Skipped),
+            attempt(open_tag("rt"))
+                .with(many1(kana_chunk()))
+                .skip(close_tag("rt"))
+                .map(RubyPart::Reading),
+            attempt(open_tag("rb")).with(text()).skip(close_tag("rb")),
+            text(),
+        ))))
+        .skip(close_tag("ruby"))
+        .and_then(|parts| -> Result<_, StreamErrorFor<Input>> {
+            let mut bases = vec![];
+            let mut readings = vec![];
+
+            for part in parts {
+                match part {
+                    RubyPart::Base(base) => bases.push(base),
+                    RubyPart::Reading(reading) => readings.push(reading),
+                    RubyPart::Skipped => {}
+                }
+            }
+
+            if bases.is_empty() || bases.len() != readings.len() {
+                return Err(StreamErrorFor::<Input>::other(Failure::UnpairedRuby {
+                    bases: bases.len(),
+                    readings: readings.len(),
+                }));
+            }
+
+            Ok(bases
+                .into_iter()
+                .zip(readings)
+                .map(|(displayed, reading)| DisplayedTypedPair {
+                    displayed,
+                    ruby: true,
+                    typed: reading.into_iter().flat_map(|pair| pair.typed).collect(),
+                })
+                .collect())
+        })
+}
+
+/// An opening tag with any attributes, like `<ruby class="word">`.
+fn open_tag<Input>(name: &'static str) -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (
+        token('<'),
+        string_cmp(name, |l: char, r: char| l.eq_ignore_ascii_case(&r)),
+        look_ahead(satisfy(|c: char| c == '>' || c.is_whitespace())),
+        skip_many(satisfy(|c| c != '>')),
+        token('>'),
+    )
+        .map(|_| ())
+}
+
+fn close_tag<Input>(name: &'static str) -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (
+        token('<'),
+        token('/'),
+        string_cmp(name, |l: char, r: char| l.eq_ignore_ascii_case(&r)),
+        token('>'),
+    )
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_html_typing_target, Error};
+
+    fn displayed(line: &str) -> Vec<String> {
+        parse_html_typing_target(line).unwrap().displayed_chunks
+    }
+
+    #[test]
+    fn reads_ruby_like_parentheses() {
+        let target = parse_html_typing_target("<ruby>漢字<rt>かんじ</rt></ruby>をかく").unwrap();
+
+        assert_eq!(target.displayed_chunks, ["漢字", "を", "か", "く"]);
+        assert_eq!(target.hint(0).unwrap(), "kanji");
+    }
+
+    #[test]
+    fn pairs_every_base_with_its_reading() {
+        assert_eq!(
+            displayed("<ruby>漢<rt>かん</rt>字<rt>じ</rt></ruby>"),
+            ["漢", "字"]
+        );
+        assert_eq!(
+            displayed(
+                "<ruby class=\"word\"><rb>漢</rb><rb>字</rb><rp>(</rp><rt>かん</rt><rt>じ</rt><rp>)</rp></ruby>"
+            ),
+            ["漢", "字"]
+        );
+        assert_eq!(
+            parse_html_typing_target("<ruby>漢字<rt>かん</rt><rt>じ</rt></ruby>").unwrap_err(),
+            Error::UnpairedRuby {
+                bases: 1,
+                readings: 2,
+                char_offset: 0,
+                byte_offset: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn skips_whitespace_between_tags() {
+        assert_eq!(
+            displayed("<ruby>\n  漢字\n  <rt>かんじ</rt>\n</ruby>"),
+            ["漢字"]
+        );
+        assert_eq!(displayed("<ruby>漢字<rt>かんじ</rt> </ruby>"), ["漢字"]);
+        assert!(matches!(
+            parse_html_typing_target("<ruby>  <rt>かんじ</rt></ruby>"),
+            Err(Error::UnpairedRuby {
+                bases: 0,
+                readings: 1,
+                ..
+            })
+        ));
+    }
+}
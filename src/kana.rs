@@ -0,0 +1,452 @@
+pub(crate) static HIRAGANA: &str = "あいうえおかがきぎくぐけげこごさざしじすずせぜそぞただちぢつづてでとどなにぬねのはばぱひびぴふぶぷへべぺほぼぽまみむめもやゆよらりるれろわゐゑをんゔ";
+pub(crate) static KATAKANA: &str = "アイウエオカガキギクグケゲコゴサザシジスズセゼソゾタダチヂツヅテデトドナニヌネノハバパヒビピフブプヘベペホボポマミムメモヤユヨラリルレロワヰヱヲンヴヷヸヹヺ";
+pub(crate) static SUTEGANA: &str = "ァィゥェォャュョヮヵヶぁぃぅぇぉゃゅょゎゕゖ";
+pub(crate) static SOKUON: &str = "っッ";
+pub(crate) static CHOONPU: &str = "ー";
+pub(crate) static PUNCTUATION: &str = "、。！？「」・〜～";
+static HALF_WIDTH: &str = "｡｢｣､･ｦｧｨｩｪｫｬｭｮｯｰｱｲｳｴｵｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾅﾆﾇﾈﾉﾊﾋﾌﾍﾎﾏﾐﾑﾒﾓﾔﾕﾖﾗﾘﾙﾚﾛﾜﾝ";
+static FULL_WIDTH: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+/// True for any character that is parsed as part of a kana chunk.
+pub(crate) fn is_kana(c: char) -> bool {
+    [HIRAGANA, KATAKANA, SUTEGANA, SOKUON, CHOONPU]
+        .iter()
+        .any(|class| class.contains(c))
+}
+
+/// True for the spaces between words, ASCII or ideographic.
+pub(crate) fn is_space(c: char) -> bool {
+    c == ' ' || c == '\u{3000}'
+}
+
+/// True if a sokuon typed as `consonant` can come before `typed`, the
+/// spelling of the kana after it, like "k" before "ka" or "t" before "chi".
+pub(crate) fn doubles(consonant: &str, typed: &str) -> bool {
+    typed.starts_with(consonant) || (consonant == "t" && typed.starts_with("ch"))
+}
+
+/// The hiragana for a katakana, or the character itself for anything else.
+pub(crate) fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// The katakana for a hiragana, or the character itself for anything else.
+pub(crate) fn to_katakana(c: char) -> char {
+    match c {
+        'ぁ'..='ゖ' => char::from_u32(c as u32 + 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// True for kanji, and for the other characters that are read together with
+/// them, like the ヶ in `一ヶ月(いっかげつ)`.
+pub(crate) fn is_ruby_base(c: char) -> bool {
+    matches!(c,
+        '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '々'
+        | '〆'
+        | '〇'
+        | 'ヶ')
+}
+
+/// True for ASCII and full-width digits.
+pub(crate) fn is_digit(c: char) -> bool {
+    matches!(c, '0'..='9' | '０'..='９')
+}
+
+/// The full-width katakana or punctuation for a half-width one, like カ for ｶ.
+pub(crate) fn to_full_width(c: char) -> Option<char> {
+    HALF_WIDTH
+        .chars()
+        .zip(FULL_WIDTH.chars())
+        .find(|(half, _)| *half == c)
+        .map(|(_, full)| full)
+}
+
+/// `kana` with the dakuten or handakuten `mark` added, like ガ for カ and ﾞ.
+/// The marks may be half-width, or the combining marks of decomposed (NFD)
+/// text.
+pub(crate) fn add_voicing_mark(kana: char, mark: char) -> Option<char> {
+    let mark = match mark {
+        '\u{3099}' => 'ﾞ',
+        '\u{309a}' => 'ﾟ',
+        _ => mark,
+    };
+
+    let offset = match mark {
+        'ﾞ' if "かきくけこさしすせそたちつてとはひふへほカキクケコサシスセソタチツテトハヒフヘホ"
+            .contains(kana) =>
+        {
+            1
+        }
+        'ﾟ' if "はひふへほハヒフヘホ".contains(kana) => 2,
+        'ﾞ' => {
+            return match kana {
+                'う' => Some('ゔ'),
+                'ウ' => Some('ヴ'),
+                'ワ' => Some('ヷ'),
+                'ヰ' => Some('ヸ'),
+                'ヱ' => Some('ヹ'),
+                'ヲ' => Some('ヺ'),
+                _ => None,
+            }
+        }
+        _ => return None,
+    };
+
+    char::from_u32(kana as u32 + offset)
+}
+
+/// `text` with half-width katakana and punctuation made full-width, and their
+/// voicing marks combined, so ｶﾞｯｺｳ becomes ガッコウ.
+pub(crate) fn normalize_half_width(text: &str) -> String {
+    let mut normalized = String::new();
+
+    for c in text.chars() {
+        let voiced = normalized
+            .chars()
+            .last()
+            .and_then(|last| add_voicing_mark(last, c));
+
+        if let Some(voiced) = voiced {
+            normalized.pop();
+            normalized.push(voiced);
+        } else {
+            normalized.push(to_full_width(c).unwrap_or(c));
+        }
+    }
+
+    normalized
+}
+
+/// The ASCII letter or digit for an ASCII or full-width letter or digit.
+pub(crate) fn to_ascii_alphanumeric(c: char) -> Option<char> {
+    match c {
+        'a'..='z' | 'A'..='Z' | '0'..='9' => Some(c),
+        'ａ'..='ｚ' | 'Ａ'..='Ｚ' | '０'..='９' => char::from_u32(c as u32 - 0xfee0),
+        _ => None,
+    }
+}
+
+/// Returns every romaji spelling that a player may type for `kana`, or the
+/// key typed for a punctuation mark like 、.
+///
+/// The first spelling is the one an IME turns into `kana`, preferring Hepburn
+/// when the IME accepts several. Kunrei-shiki, Nihon-shiki and common IME
+/// variants follow.
+pub fn kana_to_typed_chunks(kana: &str) -> Option<&'static [&'static str]> {
+    match kana {
+        // hiragana
+        "あ" => Some(&["a"]),
+        "い" => Some(&["i"]),
+        "う" => Some(&["u"]),
+        "え" => Some(&["e"]),
+        "お" => Some(&["o"]),
+        "か" => Some(&["ka"]),
+        "が" => Some(&["ga"]),
+        "き" => Some(&["ki"]),
+        "ぎ" => Some(&["gi"]),
+        "く" => Some(&["ku"]),
+        "ぐ" => Some(&["gu"]),
+        "け" => Some(&["ke"]),
+        "げ" => Some(&["ge"]),
+        "こ" => Some(&["ko"]),
+        "ご" => Some(&["go"]),
+        "さ" => Some(&["sa"]),
+        "ざ" => Some(&["za"]),
+        "し" => Some(&["shi", "si"]),
+        "じ" => Some(&["ji", "zi"]),
+        "す" => Some(&["su"]),
+        "ず" => Some(&["zu"]),
+        "せ" => Some(&["se"]),
+        "ぜ" => Some(&["ze"]),
+        "そ" => Some(&["so"]),
+        "ぞ" => Some(&["zo"]),
+        "た" => Some(&["ta"]),
+        "だ" => Some(&["da"]),
+        "ち" => Some(&["chi", "ti"]),
+        "ぢ" => Some(&["di", "ji"]),
+        "つ" => Some(&["tsu", "tu"]),
+        "づ" => Some(&["du", "dzu", "zu"]),
+        "て" => Some(&["te"]),
+        "で" => Some(&["de"]),
+        "と" => Some(&["to"]),
+        "ど" => Some(&["do"]),
+        "な" => Some(&["na"]),
+        "に" => Some(&["ni"]),
+        "ぬ" => Some(&["nu"]),
+        "ね" => Some(&["ne"]),
+        "の" => Some(&["no"]),
+        "は" => Some(&["ha"]),
+        "ば" => Some(&["ba"]),
+        "ぱ" => Some(&["pa"]),
+        "ひ" => Some(&["hi"]),
+        "び" => Some(&["bi"]),
+        "ぴ" => Some(&["pi"]),
+        "ふ" => Some(&["fu", "hu"]),
+        "ぶ" => Some(&["bu"]),
+        "ぷ" => Some(&["pu"]),
+        "へ" => Some(&["he"]),
+        "べ" => Some(&["be"]),
+        "ぺ" => Some(&["pe"]),
+        "ほ" => Some(&["ho"]),
+        "ぼ" => Some(&["bo"]),
+        "ぽ" => Some(&["po"]),
+        "ま" => Some(&["ma"]),
+        "み" => Some(&["mi"]),
+        "む" => Some(&["mu"]),
+        "め" => Some(&["me"]),
+        "も" => Some(&["mo"]),
+        "や" => Some(&["ya"]),
+        "ゆ" => Some(&["yu"]),
+        "よ" => Some(&["yo"]),
+        "ら" => Some(&["ra"]),
+        "り" => Some(&["ri"]),
+        "る" => Some(&["ru"]),
+        "れ" => Some(&["re"]),
+        "ろ" => Some(&["ro"]),
+        "わ" => Some(&["wa"]),
+        "ゐ" => Some(&["wyi", "wi"]),
+        "ゑ" => Some(&["wye", "we"]),
+        "を" => Some(&["wo"]),
+        "ゔ" => Some(&["vu"]),
+        "ん" => Some(&["nn", "n'", "xn"]),
+        "っ" => Some(&["xtu", "ltu", "xtsu", "ltsu"]),
+        // you-on
+        "きゃ" => Some(&["kya"]),
+        "きゅ" => Some(&["kyu"]),
+        "きょ" => Some(&["kyo"]),
+        "しゃ" => Some(&["sha", "sya"]),
+        "しゅ" => Some(&["shu", "syu"]),
+        "しょ" => Some(&["sho", "syo"]),
+        "ちゃ" => Some(&["cha", "tya", "cya"]),
+        "ちゅ" => Some(&["chu", "tyu", "cyu"]),
+        "ちょ" => Some(&["cho", "tyo", "cyo"]),
+        "にゃ" => Some(&["nya"]),
+        "にゅ" => Some(&["nyu"]),
+        "にょ" => Some(&["nyo"]),
+        "ひゃ" => Some(&["hya"]),
+        "ひゅ" => Some(&["hyu"]),
+        "ひょ" => Some(&["hyo"]),
+        "みゃ" => Some(&["mya"]),
+        "みゅ" => Some(&["myu"]),
+        "みょ" => Some(&["myo"]),
+        "りゃ" => Some(&["rya"]),
+        "りゅ" => Some(&["ryu"]),
+        "りょ" => Some(&["ryo"]),
+        "ぎゃ" => Some(&["gya"]),
+        "ぎゅ" => Some(&["gyu"]),
+        "ぎょ" => Some(&["gyo"]),
+        "じゃ" => Some(&["ja", "zya", "jya"]),
+        "じゅ" => Some(&["ju", "zyu", "jyu"]),
+        "じょ" => Some(&["jo", "zyo", "jyo"]),
+        "ぢゃ" => Some(&["dya"]),
+        "ぢゅ" => Some(&["dyu"]),
+        "ぢょ" => Some(&["dyo"]),
+        "びゃ" => Some(&["bya"]),
+        "びゅ" => Some(&["byu"]),
+        "びょ" => Some(&["byo"]),
+        "ぴゃ" => Some(&["pya"]),
+        "ぴゅ" => Some(&["pyu"]),
+        "ぴょ" => Some(&["pyo"]),
+        // sutegana on their own
+        "ぁ" => Some(&["xa", "la"]),
+        "ぃ" => Some(&["xi", "li"]),
+        "ぅ" => Some(&["xu", "lu"]),
+        "ぇ" => Some(&["xe", "le"]),
+        "ぉ" => Some(&["xo", "lo"]),
+        "ゃ" => Some(&["xya", "lya"]),
+        "ゅ" => Some(&["xyu", "lyu"]),
+        "ょ" => Some(&["xyo", "lyo"]),
+        "ゎ" => Some(&["xwa", "lwa"]),
+        "ゕ" => Some(&["xka", "lka"]),
+        "ゖ" => Some(&["xke", "lke"]),
+        // katakana
+        "ア" => Some(&["a"]),
+        "イ" => Some(&["i"]),
+        "ウ" => Some(&["u"]),
+        "エ" => Some(&["e"]),
+        "オ" => Some(&["o"]),
+        "カ" => Some(&["ka"]),
+        "ガ" => Some(&["ga"]),
+        "キ" => Some(&["ki"]),
+        "ギ" => Some(&["gi"]),
+        "ク" => Some(&["ku"]),
+        "グ" => Some(&["gu"]),
+        "ケ" => Some(&["ke"]),
+        "ゲ" => Some(&["ge"]),
+        "コ" => Some(&["ko"]),
+        "ゴ" => Some(&["go"]),
+        "サ" => Some(&["sa"]),
+        "ザ" => Some(&["za"]),
+        "シ" => Some(&["shi", "si"]),
+        "ジ" => Some(&["ji", "zi"]),
+        "ス" => Some(&["su"]),
+        "ズ" => Some(&["zu"]),
+        "セ" => Some(&["se"]),
+        "ゼ" => Some(&["ze"]),
+        "ソ" => Some(&["so"]),
+        "ゾ" => Some(&["zo"]),
+        "タ" => Some(&["ta"]),
+        "ダ" => Some(&["da"]),
+        "チ" => Some(&["chi", "ti"]),
+        "ヂ" => Some(&["di", "ji"]),
+        "ツ" => Some(&["tsu", "tu"]),
+        "ヅ" => Some(&["du", "dzu", "zu"]),
+        "テ" => Some(&["te"]),
+        "デ" => Some(&["de"]),
+        "ト" => Some(&["to"]),
+        "ド" => Some(&["do"]),
+        "ナ" => Some(&["na"]),
+        "ニ" => Some(&["ni"]),
+        "ヌ" => Some(&["nu"]),
+        "ネ" => Some(&["ne"]),
+        "ノ" => Some(&["no"]),
+        "ハ" => Some(&["ha"]),
+        "バ" => Some(&["ba"]),
+        "パ" => Some(&["pa"]),
+        "ヒ" => Some(&["hi"]),
+        "ビ" => Some(&["bi"]),
+        "ピ" => Some(&["pi"]),
+        "フ" => Some(&["fu", "hu"]),
+        "ブ" => Some(&["bu"]),
+        "プ" => Some(&["pu"]),
+        "ヘ" => Some(&["he"]),
+        "ベ" => Some(&["be"]),
+        "ペ" => Some(&["pe"]),
+        "ホ" => Some(&["ho"]),
+        "ボ" => Some(&["bo"]),
+        "ポ" => Some(&["po"]),
+        "マ" => Some(&["ma"]),
+        "ミ" => Some(&["mi"]),
+        "ム" => Some(&["mu"]),
+        "メ" => Some(&["me"]),
+        "モ" => Some(&["mo"]),
+        "ヤ" => Some(&["ya"]),
+        "ユ" => Some(&["yu"]),
+        "ヨ" => Some(&["yo"]),
+        "ラ" => Some(&["ra"]),
+        "リ" => Some(&["ri"]),
+        "ル" => Some(&["ru"]),
+        "レ" => Some(&["re"]),
+        "ロ" => Some(&["ro"]),
+        "ワ" => Some(&["wa"]),
+        "ヰ" => Some(&["wyi", "wi"]),
+        "ヱ" => Some(&["wye", "we"]),
+        "ヲ" => Some(&["wo"]),
+        "ヴ" => Some(&["vu"]),
+        "ヷ" => Some(&["va"]),
+        "ヸ" => Some(&["vi"]),
+        "ヹ" => Some(&["ve"]),
+        "ヺ" => Some(&["vo"]),
+        "ン" => Some(&["nn", "n'", "xn"]),
+        "ッ" => Some(&["xtu", "ltu", "xtsu", "ltsu"]),
+        // you-on
+        "キャ" => Some(&["kya"]),
+        "キュ" => Some(&["kyu"]),
+        "キョ" => Some(&["kyo"]),
+        "シャ" => Some(&["sha", "sya"]),
+        "シュ" => Some(&["shu", "syu"]),
+        "ショ" => Some(&["sho", "syo"]),
+        "チャ" => Some(&["cha", "tya", "cya"]),
+        "チュ" => Some(&["chu", "tyu", "cyu"]),
+        "チョ" => Some(&["cho", "tyo", "cyo"]),
+        "ニャ" => Some(&["nya"]),
+        "ニュ" => Some(&["nyu"]),
+        "ニョ" => Some(&["nyo"]),
+        "ヒャ" => Some(&["hya"]),
+        "ヒュ" => Some(&["hyu"]),
+        "ヒョ" => Some(&["hyo"]),
+        "ミャ" => Some(&["mya"]),
+        "ミュ" => Some(&["myu"]),
+        "ミョ" => Some(&["myo"]),
+        "リャ" => Some(&["rya"]),
+        "リュ" => Some(&["ryu"]),
+        "リョ" => Some(&["ryo"]),
+        "ギャ" => Some(&["gya"]),
+        "ギュ" => Some(&["gyu"]),
+        "ギョ" => Some(&["gyo"]),
+        "ジャ" => Some(&["ja", "zya", "jya"]),
+        "ジュ" => Some(&["ju", "zyu", "jyu"]),
+        "ジョ" => Some(&["jo", "zyo", "jyo"]),
+        "ヂャ" => Some(&["dya"]),
+        "ヂュ" => Some(&["dyu"]),
+        "ヂョ" => Some(&["dyo"]),
+        "ビャ" => Some(&["bya"]),
+        "ビュ" => Some(&["byu"]),
+        "ビョ" => Some(&["byo"]),
+        "ピャ" => Some(&["pya"]),
+        "ピュ" => Some(&["pyu"]),
+        "ピョ" => Some(&["pyo"]),
+        // sutegana on their own
+        "ァ" => Some(&["xa", "la"]),
+        "ィ" => Some(&["xi", "li"]),
+        "ゥ" => Some(&["xu", "lu"]),
+        "ェ" => Some(&["xe", "le"]),
+        "ォ" => Some(&["xo", "lo"]),
+        "ャ" => Some(&["xya", "lya"]),
+        "ュ" => Some(&["xyu", "lyu"]),
+        "ョ" => Some(&["xyo", "lyo"]),
+        "ヮ" => Some(&["xwa", "lwa"]),
+        "ヵ" => Some(&["xka", "lka"]),
+        "ヶ" => Some(&["xke", "lke"]),
+        // both scripts
+        "ー" => Some(&["-"]),
+        // wacky katakana you-on
+        "イェ" => Some(&["ye"]),
+        "ウィ" => Some(&["wi", "whi"]),
+        "ウェ" => Some(&["we", "whe"]),
+        "ウォ" => Some(&["who"]),
+        "ヴァ" => Some(&["va"]),
+        "ヴィ" => Some(&["vi"]),
+        "ヴェ" => Some(&["ve"]),
+        "ヴォ" => Some(&["vo"]),
+        "ヴュ" => Some(&["vyu"]),
+        "クァ" => Some(&["kwa", "qa"]),
+        "クヮ" => Some(&["kwa", "qa"]),
+        "クィ" => Some(&["qi", "kwi"]),
+        "クェ" => Some(&["qe", "kwe"]),
+        "クォ" => Some(&["qo", "kwo"]),
+        "グァ" => Some(&["gwa"]),
+        "グヮ" => Some(&["gwa"]),
+        "シェ" => Some(&["she", "sye"]),
+        "ジェ" => Some(&["je", "zye", "jye"]),
+        "チェ" => Some(&["che", "tye", "cye"]),
+        "ツァ" => Some(&["tsa"]),
+        "ツィ" => Some(&["tsi"]),
+        "ツェ" => Some(&["tse"]),
+        "ツォ" => Some(&["tso"]),
+        "ティ" => Some(&["thi"]),
+        "テュ" => Some(&["thu"]),
+        "ディ" => Some(&["dhi"]),
+        "デュ" => Some(&["dhu"]),
+        "トゥ" => Some(&["twu"]),
+        "ドゥ" => Some(&["dwu"]),
+        "ファ" => Some(&["fa"]),
+        "フィ" => Some(&["fi"]),
+        "フェ" => Some(&["fe"]),
+        "フォ" => Some(&["fo"]),
+        "フュ" => Some(&["fyu"]),
+        // punctuation
+        "、" => Some(&[","]),
+        "。" => Some(&["."]),
+        "！" => Some(&["!"]),
+        "？" => Some(&["?"]),
+        "「" => Some(&["["]),
+        "」" => Some(&["]"]),
+        "・" => Some(&["/"]),
+        "〜" => Some(&["~"]),
+        "～" => Some(&["~"]),
+        // the wacky you-on, written in hiragana, as in ふぁいる
+        _ if kana.chars().count() == 2 && kana.chars().all(|c| ('ぁ'..='ゖ').contains(&c)) => {
+            let katakana: String = kana.chars().map(to_katakana).collect();
+            kana_to_typed_chunks(&katakana)
+        }
+        _ => None,
+    }
+}
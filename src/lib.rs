@@ -0,0 +1,335 @@
+//! Turns lines of Japanese text into [`TypingTarget`]s: chunks to display,
+//! each with the romaji that a player may type for it.
+//!
+//! Kanji are given their reading in parentheses, like `山(やま)`, and the
+//! whole reading is typed as one chunk. A `|` before other text, as in
+//! `|山ノ内(やまのうち)`, displays all of it with the reading. Text in the
+//! Aozora Bunko format is read with [`RubySyntax::Aozora`], and HTML `<ruby>`
+//! markup with [`parse_html_typing_target`]. With the `dictionary` feature,
+//! bare kanji can be given readings from a local dictionary file.
+//!
+//! Passages of several lines and paragraphs are parsed with
+//! [`parse_passage`], and word lists with [`parse_word_list`], in plain text
+//! or, with the `toml` and `json` features, in TOML or JSON.
+//!
+//! Going the other way, [`romaji_to_kana`] turns typed romaji back into kana.
+
+use combine::stream::{easy, position};
+use combine::{eof, EasyParser, Parser};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+#[cfg(feature = "dictionary")]
+pub mod dictionary;
+mod error;
+mod furigana;
+pub mod html;
+mod kana;
+mod matcher;
+pub mod parser;
+mod passage;
+mod romaji;
+mod word_list;
+
+pub use error::Error;
+pub use furigana::{align_reading, align_typing_target};
+pub use kana::kana_to_typed_chunks;
+pub use matcher::{MatchEvent, Matcher};
+pub use parser::{DisplayedTypedPair, TypedUnit};
+pub use passage::{parse_passage, parse_passage_with_options, PassageError, PassageLine};
+pub use romaji::{romaji_to_kana, Script};
+pub use word_list::{load_word_list, parse_word_list, EntryError, WordListError, WordListFormat};
+
+/// A line split into chunks that are displayed and typed one at a time.
+///
+/// With the `serde` feature, a target is serialized with its field names, and
+/// ranges of chunks as `start` and `end`. Missing fields take their default
+/// values when deserialized. `山(やま)のぼり` with
+/// [`ParseOptions::split_readings`] becomes:
+///
+/// ```json
+/// {
+///   "displayed_chunks": ["や", "ま", "の", "ぼ", "り"],
+///   "typed_chunks": [
+///     [{"kana": "や", "typed": ["ya"]}],
+///     [{"kana": "ま", "typed": ["ma"]}],
+///     [{"kana": "の", "typed": ["no"]}],
+///     [{"kana": "ぼ", "typed": ["bo"]}],
+///     [{"kana": "り", "typed": ["ri"]}]
+///   ],
+///   "spans": [{"text": "山", "chunks": {"start": 0, "end": 2}}],
+///   "words": [{"start": 0, "end": 5}],
+///   "fixed": false,
+///   "disabled": false,
+///   "weight": 1.0,
+///   "tags": []
+/// }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct TypingTarget {
+    pub displayed_chunks: Vec<String>,
+    /// The kana typed for the displayed chunk at the same index, one after
+    /// another, each with every spelling accepted for it. The first spelling
+    /// of each is the canonical one, as put together by [`hint`].
+    ///
+    /// [`hint`]: TypingTarget::hint
+    pub typed_chunks: Vec<Vec<TypedUnit>>,
+    /// Text displayed over several chunks, like a kanji whose reading is
+    /// typed one kana at a time with [`ParseOptions::split_readings`].
+    pub spans: Vec<DisplaySpan>,
+    /// The chunks of each word, in order. The chunks between words are the
+    /// spaces that separate them.
+    pub words: Vec<Range<usize>>,
+    /// If true, do not replace the `TypingTarget` with another from the word list after it is typed.
+    pub fixed: bool,
+    /// If true, does not perform its action or make sounds when typed.
+    pub disabled: bool,
+    /// How likely the `TypingTarget` is to be picked from the word list,
+    /// relative to the others. Defaults to 1.0.
+    pub weight: f32,
+    /// Labels from the word list, for choosing which targets to use.
+    pub tags: Vec<String>,
+}
+
+impl Default for TypingTarget {
+    fn default() -> Self {
+        Self {
+            displayed_chunks: vec![],
+            typed_chunks: vec![],
+            spans: vec![],
+            words: vec![],
+            fixed: false,
+            disabled: false,
+            weight: 1.0,
+            tags: vec![],
+        }
+    }
+}
+
+impl TypingTarget {
+    /// The canonical spelling of a chunk, suitable for showing as a hint.
+    pub fn hint(&self, chunk: usize) -> Option<String> {
+        self.typed_chunks.get(chunk).map(|units| {
+            units
+                .iter()
+                .filter_map(|unit| unit.typed.first())
+                .map(String::as_str)
+                .collect()
+        })
+    }
+}
+
+/// Text displayed in place of a run of chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DisplaySpan {
+    pub text: String,
+    /// The indices of the chunks typed for `text`.
+    pub chunks: Range<usize>,
+}
+
+impl DisplaySpan {
+    /// How much of the span has been typed, from 0.0 to 1.0, when
+    /// `current_chunk` is the chunk being typed.
+    pub fn progress(&self, current_chunk: usize) -> f32 {
+        // an inverted range can come from a deserialized target
+        if self.chunks.is_empty() {
+            return 1.0;
+        }
+
+        let typed = current_chunk.clamp(self.chunks.start, self.chunks.end) - self.chunks.start;
+        typed as f32 / self.chunks.len() as f32
+    }
+}
+
+/// Settings that change how a line is split into chunks.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// If true, a long vowel mark is displayed and typed together with the
+    /// chunk before it, so コー is one chunk typed as "ko-".
+    pub fold_long_vowels: bool,
+    /// How readings are written in the line.
+    pub ruby_syntax: RubySyntax,
+    /// The opening and closing brackets that readings may be written in with
+    /// [`RubySyntax::Parenthetical`]. Defaults to `()` and `（）`.
+    pub reading_brackets: Vec<(char, char)>,
+    /// If true, the reading of a kanji is typed as one chunk per kana instead
+    /// of all at once, and the kanji is displayed as a [`DisplaySpan`] over
+    /// those chunks.
+    pub split_readings: bool,
+    /// If true, Latin letters must be typed in the case they are displayed
+    /// in. Otherwise either case is accepted.
+    pub case_sensitive: bool,
+    /// If true, full-width digits like １ must be typed as the ASCII digit.
+    /// Otherwise the full-width digit itself is accepted too, for IMEs that
+    /// type full-width.
+    pub require_ascii_digits: bool,
+    /// If true, half-width katakana like ｶﾞｯｺｳ are displayed as full-width
+    /// ガッコウ. Otherwise they are displayed as they are written.
+    pub normalize_half_width: bool,
+    /// How the spaces between words are typed. Spaces at the start and end
+    /// of a line are dropped.
+    pub spaces: Spaces,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            fold_long_vowels: false,
+            ruby_syntax: RubySyntax::default(),
+            reading_brackets: vec![('(', ')'), ('（', '）')],
+            split_readings: false,
+            case_sensitive: false,
+            require_ascii_digits: true,
+            normalize_half_width: false,
+            spaces: Spaces::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// The marker and the brackets of readings in `ruby_syntax`.
+    pub(crate) fn ruby_delimiters(&self) -> (char, Vec<(char, char)>) {
+        match self.ruby_syntax {
+            RubySyntax::Parenthetical => ('|', self.reading_brackets.clone()),
+            RubySyntax::Aozora => ('｜', vec![('《', '》')]),
+        }
+    }
+}
+
+/// The ways of writing a reading after the text it belongs to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RubySyntax {
+    /// `山(やま)`, with `|` marking where the text starts when it isn't only
+    /// kanji, as in `|山ノ内(やまのうち)`. Other brackets can be set in
+    /// [`ParseOptions::reading_brackets`].
+    #[default]
+    Parenthetical,
+    /// Aozora Bunko's `山《やま》`, with `｜` marking where the text starts, as
+    /// in `｜山ノ内《やまのうち》`. Annotations like `［＃「猫」に傍点］` are
+    /// skipped.
+    Aozora,
+}
+
+/// How the spaces between words are typed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Spaces {
+    /// A space may be typed, or skipped by going on to the next word.
+    #[default]
+    Optional,
+    /// A space must be typed.
+    Required,
+}
+
+/// Parses a whole line, failing if any of it is left over.
+pub fn parse_typing_target(line: &str) -> Result<TypingTarget, Error> {
+    parse_typing_target_with_options(line, &ParseOptions::default())
+}
+
+/// Like [`parse_typing_target`], with settings other than the defaults.
+pub fn parse_typing_target_with_options(
+    line: &str,
+    options: &ParseOptions,
+) -> Result<TypingTarget, Error> {
+    parse_line(line, parser::japanese_with_options(options.clone()))
+}
+
+/// Parses a line of kana and HTML `<ruby>` elements, like
+/// `<ruby>漢字<rt>かんじ</rt></ruby>です`.
+pub fn parse_html_typing_target(line: &str) -> Result<TypingTarget, Error> {
+    parse_html_typing_target_with_options(line, &ParseOptions::default())
+}
+
+/// Like [`parse_html_typing_target`], with settings other than the defaults.
+pub fn parse_html_typing_target_with_options(
+    line: &str,
+    options: &ParseOptions,
+) -> Result<TypingTarget, Error> {
+    parse_line(line, html::html_with_options(options.clone()))
+}
+
+type LineStream<'a> = easy::Stream<position::Stream<&'a str, position::IndexPositioner>>;
+
+fn parse_line<'a, P>(line: &'a str, parser: P) -> Result<TypingTarget, Error>
+where
+    P: Parser<LineStream<'a>, Output = TypingTarget>,
+{
+    parser
+        .skip(eof())
+        .easy_parse(position::Stream::with_positioner(
+            line,
+            position::IndexPositioner::default(),
+        ))
+        .map(|(target, _)| target)
+        .map_err(|errors| Error::from_parse_errors(line, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverted_spans_count_as_typed() {
+        let span = DisplaySpan {
+            text: "山".to_owned(),
+            chunks: Range { start: 3, end: 1 },
+        };
+
+        assert_eq!(span.progress(0), 1.0);
+        assert_eq!(span.progress(2), 1.0);
+    }
+
+    #[test]
+    fn split_readings_are_spanned_by_their_text() {
+        let options = ParseOptions {
+            split_readings: true,
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options("|1日(ついたち)です", &options).unwrap();
+
+        assert_eq!(
+            target.displayed_chunks,
+            ["つ", "い", "た", "ち", "で", "す"]
+        );
+        assert_eq!(
+            target.spans,
+            [DisplaySpan {
+                text: "1日".to_owned(),
+                chunks: 0..4,
+            }]
+        );
+        assert_eq!(target.spans[0].progress(0), 0.0);
+        assert_eq!(target.spans[0].progress(1), 0.25);
+        assert_eq!(target.spans[0].progress(5), 1.0);
+        assert!(parse_typing_target("1日(ついたち)")
+            .unwrap()
+            .spans
+            .is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn targets_survive_a_serde_round_trip() {
+        let options = ParseOptions {
+            split_readings: true,
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options("山(やま)のぼり", &options).unwrap();
+        let json = serde_json::to_string(&target).unwrap();
+        let parsed: TypingTarget = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.displayed_chunks, target.displayed_chunks);
+        assert_eq!(parsed.hint(0), target.hint(0));
+        assert_eq!(parsed.spans[0].chunks, 0..2);
+        assert_eq!(parsed.words, target.words);
+
+        let parsed: TypingTarget = serde_json::from_str(
+            r#"{"spans": [{"text": "山", "chunks": {"start": 3, "end": 1}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.weight, 1.0);
+        assert_eq!(parsed.spans[0].progress(2), 1.0);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::kana::doubles;
+use crate::{TypedUnit, TypingTarget};
+
+/// What happened to a character fed to a [`Matcher`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchEvent {
+    /// The character does not continue any accepted input. Nothing changed.
+    Rejected,
+    /// The character continues an accepted input of the current chunk.
+    Accepted,
+    /// The character completed one or more chunks, but not the last one.
+    ChunkComplete,
+    /// The character completed the last chunk.
+    TargetComplete,
+}
+
+/// Checks keystrokes against a [`TypingTarget`] one character at a time.
+///
+/// Each chunk is typed one kana after another. All spellings of the current
+/// kana are kept as candidates until the typed characters only fit some of
+/// them, so a player may type "shi" or "si" without choosing up front. A kana
+/// whose input is also the start of a longer spelling (like "n" and "nn")
+/// stays open until the next character shows which one was meant. A sokuon
+/// typed as a doubled consonant only lets through the spellings of the next
+/// kana that start with that consonant.
+#[derive(Clone, Debug)]
+pub struct Matcher {
+    target: TypingTarget,
+    chunk: usize,
+    unit: usize,
+    input: String,
+    unit_input: String,
+    /// The consonant the sokuon before the current kana was typed as.
+    doubled: Option<String>,
+}
+
+impl Matcher {
+    pub fn new(target: TypingTarget) -> Self {
+        Self {
+            target,
+            chunk: 0,
+            unit: 0,
+            input: String::new(),
+            unit_input: String::new(),
+            doubled: None,
+        }
+    }
+
+    pub fn target(&self) -> &TypingTarget {
+        &self.target
+    }
+
+    /// The index of the chunk being typed. Equal to the number of chunks once
+    /// the target is complete.
+    pub fn current_chunk(&self) -> usize {
+        self.chunk
+    }
+
+    /// What has been typed so far for the current chunk.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The spellings of the kana being typed that are still possible.
+    pub fn candidates(&self) -> impl Iterator<Item = &str> {
+        let input = &self.unit_input;
+
+        self.spellings()
+            .into_iter()
+            .filter(move |typed| typed.starts_with(input.as_str()))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.chunk >= self.target.typed_chunks.len()
+    }
+
+    /// Feeds one typed character to the matcher.
+    pub fn push(&mut self, c: char) -> MatchEvent {
+        let saved = (
+            self.chunk,
+            self.unit,
+            self.input.clone(),
+            self.unit_input.clone(),
+            self.doubled.clone(),
+        );
+
+        let mut completed = false;
+
+        while self.current_unit().is_some() {
+            let accepted = self.spellings();
+            let is_last = self.is_last_unit();
+
+            let mut extended = self.unit_input.clone();
+            extended.push(c);
+
+            if accepted.iter().any(|typed| typed.starts_with(&extended)) {
+                let is_exact = accepted.contains(&extended.as_str());
+                let is_prefix = accepted
+                    .iter()
+                    .any(|typed| typed.len() > extended.len() && typed.starts_with(&extended));
+
+                self.unit_input = extended;
+                self.input.push(c);
+
+                if is_exact && (is_last || !is_prefix) {
+                    completed |= self.next_unit();
+                }
+
+                return if self.is_complete() {
+                    MatchEvent::TargetComplete
+                } else if completed {
+                    MatchEvent::ChunkComplete
+                } else {
+                    MatchEvent::Accepted
+                };
+            }
+
+            // the kana was left open because its input could have continued,
+            // but this character can only belong to the next one.
+            if is_last || !accepted.contains(&self.unit_input.as_str()) {
+                break;
+            }
+
+            completed |= self.next_unit();
+        }
+
+        (
+            self.chunk,
+            self.unit,
+            self.input,
+            self.unit_input,
+            self.doubled,
+        ) = saved;
+
+        MatchEvent::Rejected
+    }
+
+    fn current_unit(&self) -> Option<&TypedUnit> {
+        self.target.typed_chunks.get(self.chunk)?.get(self.unit)
+    }
+
+    /// The spellings of the current kana that fit the sokuon before it.
+    fn spellings(&self) -> Vec<&str> {
+        let doubled = self.doubled.as_deref();
+
+        self.current_unit()
+            .into_iter()
+            .flat_map(|unit| &unit.typed)
+            .map(String::as_str)
+            .filter(|typed| doubled.is_none_or(|consonant| doubles(consonant, typed)))
+            .collect()
+    }
+
+    fn is_last_unit(&self) -> bool {
+        self.chunk + 1 == self.target.typed_chunks.len()
+            && self.unit + 1 == self.target.typed_chunks[self.chunk].len()
+    }
+
+    /// Moves on to the next kana, returning true if that completed a chunk.
+    fn next_unit(&mut self) -> bool {
+        // a sokuon's spellings other than the doubled consonants, like
+        // "xtu", are longer than one letter
+        let is_sokuon = self.current_unit().is_some_and(TypedUnit::is_sokuon);
+        self.doubled = (is_sokuon && self.unit_input.len() == 1).then(|| self.unit_input.clone());
+
+        self.unit += 1;
+        self.unit_input.clear();
+
+        if self.unit < self.target.typed_chunks[self.chunk].len() {
+            return false;
+        }
+
+        self.chunk += 1;
+        self.unit = 0;
+        self.input.clear();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_typing_target;
+
+    fn matcher(line: &str) -> Matcher {
+        Matcher::new(parse_typing_target(line).unwrap())
+    }
+
+    /// Types `input`, stopping at the first rejected character.
+    fn type_all(matcher: &mut Matcher, input: &str) -> Vec<MatchEvent> {
+        let mut events = vec![];
+
+        for c in input.chars() {
+            let event = matcher.push(c);
+            events.push(event);
+            if event == MatchEvent::Rejected {
+                break;
+            }
+        }
+
+        events
+    }
+
+    fn completes(line: &str, input: &str) -> bool {
+        let mut matcher = matcher(line);
+        type_all(&mut matcher, input).last() == Some(&MatchEvent::TargetComplete)
+    }
+
+    #[test]
+    fn keeps_every_spelling_until_one_is_chosen() {
+        assert!(completes("すし", "sushi"));
+        assert!(completes("すし", "susi"));
+
+        let mut matcher = matcher("すし");
+        type_all(&mut matcher, "s");
+        let candidates: Vec<&str> = matcher.candidates().collect();
+        assert_eq!(candidates, ["su"]);
+
+        type_all(&mut matcher, "us");
+        let candidates: Vec<&str> = matcher.candidates().collect();
+        assert_eq!(candidates, ["shi", "si"]);
+    }
+
+    #[test]
+    fn reports_completed_chunks() {
+        let mut matcher = matcher("ねこ");
+
+        assert_eq!(
+            type_all(&mut matcher, "neko"),
+            [
+                MatchEvent::Accepted,
+                MatchEvent::ChunkComplete,
+                MatchEvent::Accepted,
+                MatchEvent::TargetComplete,
+            ]
+        );
+        assert!(matcher.is_complete());
+        assert_eq!(matcher.current_chunk(), 2);
+    }
+
+    #[test]
+    fn rejected_characters_change_nothing() {
+        let mut matcher = matcher("ねこ");
+        type_all(&mut matcher, "nek");
+
+        assert_eq!(matcher.push('a'), MatchEvent::Rejected);
+        assert_eq!(matcher.current_chunk(), 1);
+        assert_eq!(matcher.input(), "k");
+        assert_eq!(matcher.push('o'), MatchEvent::TargetComplete);
+    }
+
+    #[test]
+    fn leaves_n_open_until_the_next_character() {
+        let mut matcher = matcher("かんじ");
+        type_all(&mut matcher, "kan");
+
+        assert_eq!(matcher.current_chunk(), 1);
+        assert_eq!(matcher.push('j'), MatchEvent::ChunkComplete);
+        assert_eq!(matcher.current_chunk(), 2);
+
+        assert!(completes("かんじ", "kannji"));
+        assert!(!completes("ほん", "hon"));
+        assert!(completes("ほん", "honn"));
+    }
+
+    #[test]
+    fn needs_a_second_n_before_vowels_and_n_syllables() {
+        assert!(!completes("かんい", "kani"));
+        assert!(completes("かんい", "kan'i"));
+        assert!(!completes("こんにちは", "konichiha"));
+        assert!(completes("こんにちは", "konnnichiha"));
+    }
+
+    #[test]
+    fn doubled_consonants_must_match_the_next_kana() {
+        assert!(completes("きって", "kitte"));
+        assert!(completes("きって", "kixtute"));
+        assert!(!completes("きって", "kikte"));
+
+        assert!(completes("まっち", "macchi"));
+        assert!(completes("まっち", "matchi"));
+        assert!(completes("まっち", "matti"));
+        assert!(!completes("まっち", "macti"));
+
+        assert!(completes("ッファ", "ffa"));
+        assert!(!completes("ッファ", "hfa"));
+    }
+
+    #[test]
+    fn optional_spaces_may_be_skipped() {
+        assert!(completes("ね こ", "ne ko"));
+        assert!(completes("ね こ", "neko"));
+    }
+}
@@ -0,0 +1,961 @@
+//! The [`combine`] parsers behind [`parse_typing_target`](crate::parse_typing_target),
+//! for use as parts of larger grammars.
+
+use combine::error::StreamError;
+use combine::parser::token::token;
+use combine::stream::StreamErrorFor;
+use combine::{
+    attempt, choice, look_ahead, many1, optional, satisfy, satisfy_map, skip_many, ParseError,
+    Parser, Stream,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::Failure;
+use crate::kana::{
+    add_voicing_mark, is_digit, is_kana, is_ruby_base, is_space, kana_to_typed_chunks,
+    normalize_half_width, to_ascii_alphanumeric, to_full_width, CHOONPU, HIRAGANA, KATAKANA,
+    PUNCTUATION, SOKUON, SUTEGANA,
+};
+use crate::{DisplaySpan, ParseOptions, RubySyntax, Spaces, TypingTarget};
+
+/// One displayed chunk and the units that are typed for it.
+///
+/// With the `serde` feature, a pair is serialized with its field names, like
+/// `{"displayed": "山", "ruby": true, "typed": [{"kana": "や", "typed": ["ya"]},
+/// {"kana": "ま", "typed": ["ma"]}]}`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DisplayedTypedPair {
+    pub displayed: String,
+    /// True if `displayed` is text with a reading, rather than the kana that
+    /// are typed.
+    pub ruby: bool,
+    /// The kana behind `displayed`, typed one after another.
+    pub typed: Vec<TypedUnit>,
+}
+
+impl DisplayedTypedPair {
+    fn is_space(&self) -> bool {
+        !self.ruby && self.displayed.chars().all(is_space)
+    }
+
+    fn is_kana(&self) -> bool {
+        !self.ruby && self.typed.iter().all(|unit| unit.kana.chars().all(is_kana))
+    }
+
+    fn is_long_vowel(&self) -> bool {
+        !self.ruby
+            && self
+                .typed
+                .iter()
+                .all(|unit| CHOONPU.contains(unit.kana.as_str()))
+    }
+}
+
+/// A kana, or a kana combination, with every spelling accepted for it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TypedUnit {
+    pub kana: String,
+    pub typed: Vec<String>,
+}
+
+impl TypedUnit {
+    fn is_n(&self) -> bool {
+        self.kana == "ん" || self.kana == "ン"
+    }
+
+    pub(crate) fn is_sokuon(&self) -> bool {
+        SOKUON.contains(self.kana.as_str())
+    }
+
+    /// The consonants that a sokuon before this unit may be typed as.
+    fn doubled_consonants(&self) -> Vec<String> {
+        let mut doubled: Vec<String> = vec![];
+
+        for typed in &self.typed {
+            let first = match typed.chars().next() {
+                // x and l start the spellings of small kana, which cannot be
+                // doubled.
+                Some(c) if c.is_ascii_lowercase() && !"aiueonxl".contains(c) => c,
+                _ => continue,
+            };
+
+            // っち may be typed as "cchi" or as "tchi".
+            let mut consonants = vec![first];
+            if typed.starts_with("ch") {
+                consonants.push('t');
+            }
+
+            for consonant in consonants {
+                if !doubled.iter().any(|d| d.starts_with(consonant)) {
+                    doubled.push(consonant.into());
+                }
+            }
+        }
+
+        doubled
+    }
+
+    /// True if a single "n" typed just before this unit can only mean ん,
+    /// as before a consonant or a punctuation mark like "," for 、.
+    fn follows_single_n(&self) -> bool {
+        !self.typed.is_empty()
+            && self
+                .typed
+                .iter()
+                .all(|typed| typed.starts_with(|c: char| !"aiueony".contains(c)))
+    }
+}
+
+/// Adds the spellings that depend on the unit that follows, which can be in
+/// the next pair.
+fn resolve_context(pairs: &mut [DisplayedTypedPair]) {
+    let mut units: Vec<&mut TypedUnit> = pairs.iter_mut().flat_map(|p| &mut p.typed).collect();
+
+    for i in (0..units.len()).rev() {
+        let (current, rest) = units[i..].split_first_mut().unwrap();
+
+        // ん only needs to be typed "nn" when a lone "n" would run into the
+        // next kana, as in こんや, but not in おんがく.
+        if current.is_n() && rest.first().is_some_and(|next| next.follows_single_n()) {
+            current.typed.insert(0, "n".to_owned());
+        }
+
+        // a sokuon before anything that can't be doubled, like a vowel or the
+        // end of the word, can still be typed on its own as "xtu".
+        if current.is_sokuon() {
+            if let Some(next) = rest.first() {
+                let doubled = next.doubled_consonants();
+                current.typed.splice(0..0, doubled);
+            }
+        }
+    }
+}
+
+/// A line of kana, punctuation, Latin letters and digits, and kanji with their
+/// readings, in words that may be separated by spaces.
+pub fn japanese<Input>() -> impl Parser<Input, Output = TypingTarget>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    japanese_with_options(ParseOptions::default())
+}
+
+/// Like [`japanese`], with settings other than the defaults.
+pub fn japanese_with_options<Input>(
+    options: ParseOptions,
+) -> impl Parser<Input, Output = TypingTarget>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let skip_annotations = options.ruby_syntax == RubySyntax::Aozora;
+
+    many1::<Vec<Option<DisplayedTypedPair>>, _, _>(choice((
+        kana_chunk().map(Some),
+        punctuation_chunk().map(Some),
+        {
+            let (marker, brackets) = options.ruby_delimiters();
+            ruby(marker, brackets).map(Some)
+        },
+        // digits are only displayed with a reading when one follows them
+        latin_chunk(options.case_sensitive, options.require_ascii_digits).map(Some),
+        space_chunk(options.spaces).map(Some),
+        missing_reading().map(Some),
+        satisfy(move |c| skip_annotations && c == '［')
+            .with(aozora_annotation_body())
+            .map(|_| None),
+    )))
+    // skipped annotations leave nothing behind
+    .and_then(move |pairs| -> Result<_, StreamErrorFor<Input>> {
+        typing_target(pairs.into_iter().flatten().collect(), &options)
+            .map_err(StreamErrorFor::<Input>::other)
+    })
+}
+
+/// Puts parsed pairs together into a target, which must have something to
+/// type.
+pub(crate) fn typing_target(
+    pairs: Vec<DisplayedTypedPair>,
+    options: &ParseOptions,
+) -> Result<TypingTarget, Failure> {
+    let mut typed_chunks = vec![];
+    let mut displayed_chunks = vec![];
+    let mut spans = vec![];
+
+    let mut pairs = if options.fold_long_vowels {
+        fold_long_vowels(pairs)
+    } else {
+        pairs
+    };
+
+    if options.normalize_half_width {
+        for pair in &mut pairs {
+            pair.displayed = normalize_half_width(&pair.displayed);
+        }
+    }
+
+    // spaces only separate words, so there is nothing to type at the ends
+    while pairs.first().is_some_and(|pair| pair.is_space()) {
+        pairs.remove(0);
+    }
+    while pairs.last().is_some_and(|pair| pair.is_space()) {
+        pairs.pop();
+    }
+    if pairs.is_empty() {
+        return Err(Failure::NothingToType);
+    }
+
+    resolve_context(&mut pairs);
+
+    let mut words = vec![];
+    let mut word_start = None;
+
+    for f in pairs {
+        if f.is_space() {
+            if let Some(start) = word_start.take() {
+                words.push(start..typed_chunks.len());
+            }
+        } else {
+            word_start.get_or_insert(typed_chunks.len());
+        }
+
+        if f.ruby && options.split_readings {
+            let start = typed_chunks.len();
+
+            for unit in f.typed {
+                displayed_chunks.push(unit.kana.clone());
+                typed_chunks.push(vec![unit]);
+            }
+
+            spans.push(DisplaySpan {
+                text: f.displayed,
+                chunks: start..typed_chunks.len(),
+            });
+        } else {
+            typed_chunks.push(f.typed);
+            displayed_chunks.push(f.displayed);
+        }
+    }
+
+    if let Some(start) = word_start {
+        words.push(start..typed_chunks.len());
+    }
+
+    Ok(TypingTarget {
+        typed_chunks,
+        displayed_chunks,
+        spans,
+        words,
+        ..Default::default()
+    })
+}
+
+/// Merges every long vowel mark into the kana before it. Marks after anything
+/// else, like a space or punctuation, are left as chunks of their own.
+fn fold_long_vowels(pairs: Vec<DisplayedTypedPair>) -> Vec<DisplayedTypedPair> {
+    let mut folded: Vec<DisplayedTypedPair> = vec![];
+
+    for pair in pairs {
+        match folded.last_mut() {
+            Some(previous) if pair.is_long_vowel() && previous.is_kana() => {
+                previous.displayed.push_str(&pair.displayed);
+                previous.typed.extend(pair.typed);
+            }
+            _ => folded.push(pair),
+        }
+    }
+
+    folded
+}
+
+/// Text followed by its reading in parentheses, like `山(やま)`.
+pub fn parenthetical<Input>() -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let (marker, brackets) = ParseOptions::default().ruby_delimiters();
+    ruby(marker, brackets)
+}
+
+/// Text followed by its reading in any of the pairs of `brackets`. The reading
+/// may not be empty.
+///
+/// Nothing is consumed unless a reading follows, so digits without one can be
+/// parsed by [`latin_chunk`] instead.
+///
+/// Without a marker, only the run of kanji right before the reading is
+/// displayed with it, so in `ノ内(うち)` the ノ is left to be typed on its own,
+/// and so are the digits of `2023年(ねん)`. Digits only take a reading of
+/// their own, as in `11(じゅういち)`. Anything between the marker and the
+/// reading is displayed with it, as in `|山ノ内(やまのうち)` or `|1日(ついたち)`.
+pub fn ruby<Input>(
+    marker: char,
+    brackets: Vec<(char, char)>,
+) -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    let openers: Vec<char> = brackets.iter().map(|(open, _)| *open).collect();
+    let closers: Vec<char> = brackets.iter().map(|(_, close)| *close).collect();
+
+    attempt(
+        choice((
+            token(marker).with(many1(satisfy({
+                let openers = openers.clone();
+                move |c| !openers.contains(&c)
+            }))),
+            many1(satisfy(is_ruby_base)),
+            many1(satisfy(is_digit)),
+        ))
+        .skip(look_ahead(satisfy({
+            let openers = openers.clone();
+            move |c| openers.contains(&c)
+        }))),
+    )
+    .and(satisfy(move |c| openers.contains(&c)).then(move |open| {
+        let expected = brackets
+            .iter()
+            .find(|(o, _)| *o == open)
+            .map(|(_, close)| *close)
+            .unwrap();
+        let closers = closers.clone();
+
+        many1::<Vec<DisplayedTypedPair>, _, _>(kana_chunk()).skip(
+            satisfy(move |c| closers.contains(&c)).and_then(
+                move |found| -> Result<_, StreamErrorFor<Input>> {
+                    if found == expected {
+                        Ok(found)
+                    } else {
+                        Err(StreamErrorFor::<Input>::other(Failure::MismatchedBracket {
+                            expected,
+                            found,
+                        }))
+                    }
+                },
+            ),
+        )
+    }))
+    .map(|(outside, inside): (String, _)| DisplayedTypedPair {
+        ruby: true,
+        // anything in a parenthetical has to be typed as one chunk, even
+        // if it is composed of multiple kana.
+        typed: inside.into_iter().flat_map(|pair| pair.typed).collect(),
+        displayed: outside,
+    })
+}
+
+/// An Aozora Bunko annotation like `［＃「猫」に傍点］`.
+pub fn aozora_annotation<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    token('［').with(aozora_annotation_body())
+}
+
+/// Everything in an annotation after the opening bracket.
+fn aozora_annotation_body<Input>() -> impl Parser<Input, Output = ()>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (token('＃'), skip_many(satisfy(|c| c != '］')), token('］')).map(|_| ())
+}
+
+/// A single kana, possibly combined with the sutegana after it.
+///
+/// Half-width katakana like ｶﾞ are read as their full-width kana, but
+/// displayed as they were written.
+pub fn kana_chunk<Input>() -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kana_char()
+        .then(|(kana, source)| {
+            optional(attempt(combining_sutegana(kana)))
+                .map(move |sutegana| (kana, source.clone(), sutegana))
+        })
+        .and_then(
+            |(kana, mut displayed, sutegana): (char, String, Option<(char, String)>)|
+             -> Result<_, StreamErrorFor<Input>> {
+                let mut combined = String::from(kana);
+                if let Some((sutegana, source)) = &sutegana {
+                    combined.push(*sutegana);
+                    displayed.push_str(source);
+                }
+
+                let unknown =
+                    || StreamErrorFor::<Input>::other(Failure::UnknownKana(displayed.clone()));
+
+                let mut typed: Vec<String> = kana_to_typed_chunks(&combined)
+                    .ok_or_else(unknown)?
+                    .iter()
+                    .map(|t| (*t).to_owned())
+                    .collect();
+
+                // any combination may also be typed as its kana followed by the
+                // sutegana on its own, like "fuxa" for ファ.
+                if let Some((sutegana, _)) = sutegana {
+                    let kana = kana_to_typed_chunks(&kana.to_string()).ok_or_else(unknown)?;
+                    let sutegana =
+                        kana_to_typed_chunks(&sutegana.to_string()).ok_or_else(unknown)?;
+
+                    for k in kana {
+                        for s in sutegana {
+                            typed.push(format!("{}{}", k, s));
+                        }
+                    }
+                }
+
+                Ok(DisplayedTypedPair {
+                    ruby: false,
+                    typed: vec![TypedUnit {
+                        kana: combined,
+                        typed,
+                    }],
+                    displayed,
+                })
+            },
+        )
+}
+
+/// Any kana on its own, with the text it was written as.
+///
+/// A voicing mark after the kana is combined with it, and fails as an unknown
+/// kana if the kana cannot take it, as in あ followed by U+3099. Half-width
+/// katakana are made full-width, but keep the text they were written as,
+/// while decomposed (NFD) kana like か followed by U+3099 are written as the
+/// composed が.
+fn kana_char<Input>() -> impl Parser<Input, Output = (char, String)>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    (
+        choice((
+            satisfy(is_kana).map(|c: char| (c, None)),
+            satisfy_map(|c| {
+                to_full_width(c)
+                    .filter(|full| is_kana(*full))
+                    .map(|full| (full, Some(c)))
+            }),
+        )),
+        optional(satisfy(|mark| "\u{3099}\u{309a}ﾞﾟ".contains(mark))),
+    )
+        .and_then(
+            |((kana, half_width), mark): ((char, Option<char>), Option<char>)|
+             -> Result<_, StreamErrorFor<Input>> {
+                let source: String = half_width.unwrap_or(kana).to_string();
+
+                match mark {
+                    None => Ok((kana, source)),
+                    Some(mark) => match add_voicing_mark(kana, mark) {
+                        Some(voiced) if half_width.is_some() => Ok((voiced, source + &mark.to_string())),
+                        Some(voiced) => Ok((voiced, voiced.to_string())),
+                        None => Err(StreamErrorFor::<Input>::other(Failure::UnknownKana(
+                            source + &mark.to_string(),
+                        ))),
+                    },
+                }
+            },
+        )
+}
+
+/// A punctuation mark like 、 or 「, typed as the key that an IME turns into it.
+pub fn punctuation_chunk<Input>() -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    satisfy_map(|c| {
+        let mark = to_full_width(c).unwrap_or(c);
+        Some((mark, c)).filter(|_| PUNCTUATION.contains(mark))
+    })
+    .map(|(mark, c): (char, char)| {
+        let displayed = c.to_string();
+        let typed = kana_to_typed_chunks(&mark.to_string())
+            .unwrap_or_default()
+            .iter()
+            .map(|t| (*t).to_owned())
+            .collect();
+
+        DisplayedTypedPair {
+            ruby: false,
+            typed: vec![TypedUnit {
+                kana: mark.to_string(),
+                typed,
+            }],
+            displayed,
+        }
+    })
+}
+
+/// A Latin letter or digit, ASCII or full-width, typed as the ASCII letter or
+/// digit.
+///
+/// Letters may be typed in either case unless `case_sensitive`, and full-width
+/// digits may also be typed as themselves unless `require_ascii_digits`.
+pub fn latin_chunk<Input>(
+    case_sensitive: bool,
+    require_ascii_digits: bool,
+) -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    satisfy(|c| to_ascii_alphanumeric(c).is_some()).map(move |c: char| {
+        let ascii = to_ascii_alphanumeric(c).unwrap_or(c);
+        let mut typed = vec![ascii.to_string()];
+
+        if !case_sensitive && ascii.is_ascii_alphabetic() {
+            if ascii.is_ascii_uppercase() {
+                typed.push(ascii.to_ascii_lowercase().to_string());
+            } else {
+                typed.push(ascii.to_ascii_uppercase().to_string());
+            }
+        }
+
+        if !require_ascii_digits && ascii.is_ascii_digit() && ascii != c {
+            typed.push(c.to_string());
+        }
+
+        let displayed = c.to_string();
+
+        DisplayedTypedPair {
+            ruby: false,
+            typed: vec![TypedUnit {
+                kana: displayed.clone(),
+                typed,
+            }],
+            displayed,
+        }
+    })
+}
+
+/// A run of spaces between words, ASCII or ideographic, typed as one space.
+pub fn space_chunk<Input>(spaces: Spaces) -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    many1(satisfy(is_space)).map(move |displayed: String| {
+        let mut typed = vec![" ".to_owned()];
+        if spaces == Spaces::Optional {
+            typed.push(String::new());
+        }
+
+        DisplayedTypedPair {
+            ruby: false,
+            typed: vec![TypedUnit {
+                kana: displayed.clone(),
+                typed,
+            }],
+            displayed,
+        }
+    })
+}
+
+/// Fails on a run of kanji that has no reading, pointing at the start of the
+/// run rather than wherever a reading was looked for.
+pub(crate) fn missing_reading<Input>() -> impl Parser<Input, Output = DisplayedTypedPair>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    many1(satisfy(is_ruby_base)).and_then(|kanji: String| -> Result<_, StreamErrorFor<Input>> {
+        Err(StreamErrorFor::<Input>::other(Failure::MissingReading(
+            kanji,
+        )))
+    })
+}
+
+/// A sutegana that combines with `kana` into a single chunk.
+///
+/// Sutegana that don't combine, like the emphasis of アァ or the ゃ of あゃ,
+/// are left to be parsed as chunks of their own.
+fn combining_sutegana<Input>(kana: char) -> impl Parser<Input, Output = (char, String)>
+where
+    Input: Stream<Token = char>,
+    Input::Error: ParseError<Input::Token, Input::Range, Input::Position>,
+{
+    kana_char().and_then(move |(sutegana, source)| {
+        let combined: String = [kana, sutegana].iter().collect();
+
+        // a sokuon is typed by doubling whatever comes next, which is left to
+        // `resolve_context`, so only plain kana combine.
+        let combines = (HIRAGANA.contains(kana) || KATAKANA.contains(kana))
+            && SUTEGANA.contains(sutegana)
+            && kana_to_typed_chunks(&combined).is_some();
+
+        if combines {
+            Ok((sutegana, source))
+        } else {
+            Err(StreamErrorFor::<Input>::unexpected_static_message(
+                "sutegana that does not combine",
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse_html_typing_target, parse_typing_target, parse_typing_target_with_options, Error,
+        ParseOptions, RubySyntax, Spaces,
+    };
+
+    /// The spellings of the `unit`th kana of the `chunk`th chunk of `line`.
+    fn spellings(line: &str, chunk: usize, unit: usize) -> Vec<String> {
+        parse_typing_target(line).unwrap().typed_chunks[chunk][unit]
+            .typed
+            .clone()
+    }
+
+    #[test]
+    fn n_may_be_single_before_consonants() {
+        assert_eq!(spellings("かんじ", 1, 0), ["n", "nn", "n'", "xn"]);
+        assert_eq!(spellings("ほんや", 1, 0), ["nn", "n'", "xn"]);
+        assert_eq!(spellings("かんい", 1, 0), ["nn", "n'", "xn"]);
+        assert_eq!(spellings("こんにちは", 1, 0), ["nn", "n'", "xn"]);
+        assert_eq!(spellings("ほん", 1, 0), ["nn", "n'", "xn"]);
+    }
+
+    #[test]
+    fn n_looks_into_the_next_chunk() {
+        assert_eq!(spellings("ほん屋(や)", 1, 0), ["nn", "n'", "xn"]);
+        assert_eq!(spellings("ほん本(ほん)", 1, 0), ["n", "nn", "n'", "xn"]);
+    }
+
+    #[test]
+    fn sokuon_doubles_the_next_consonant() {
+        assert_eq!(
+            spellings("きって", 1, 0),
+            ["t", "xtu", "ltu", "xtsu", "ltsu"]
+        );
+        assert_eq!(
+            spellings("まっち", 1, 0),
+            ["c", "t", "xtu", "ltu", "xtsu", "ltsu"]
+        );
+        assert_eq!(spellings("あっ", 1, 0), ["xtu", "ltu", "xtsu", "ltsu"]);
+        assert_eq!(spellings("あっあ", 1, 0), ["xtu", "ltu", "xtsu", "ltsu"]);
+    }
+
+    #[test]
+    fn readings_may_use_other_brackets() {
+        let options = ParseOptions {
+            reading_brackets: vec![('(', ')'), ('（', '）'), ('【', '】')],
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options("東(ひがし)京【きょう】", &options).unwrap();
+
+        assert_eq!(target.displayed_chunks, ["東", "京"]);
+        assert_eq!(target.hint(1).unwrap(), "kyou");
+        assert_eq!(
+            parse_typing_target("東京（とうきょう）")
+                .unwrap()
+                .displayed_chunks,
+            ["東京"]
+        );
+        assert_eq!(
+            parse_typing_target_with_options("東(ひがし】", &options).unwrap_err(),
+            Error::MismatchedBracket {
+                expected: ')',
+                found: '】',
+                char_offset: 5,
+                byte_offset: 13,
+            }
+        );
+    }
+
+    #[test]
+    fn ruby_needs_a_reading() {
+        assert!(matches!(
+            parse_typing_target("山()"),
+            Err(Error::Unexpected {
+                found: Some(')'),
+                char_offset: 2,
+                ..
+            })
+        ));
+        assert!(parse_html_typing_target("<ruby>山<rt></rt></ruby>").is_err());
+        assert!(parse_html_typing_target("<ruby>山<rt>やま</rt></ruby>").is_ok());
+    }
+
+    #[test]
+    fn lines_need_something_to_type() {
+        let aozora = ParseOptions {
+            ruby_syntax: RubySyntax::Aozora,
+            ..Default::default()
+        };
+
+        assert_eq!(parse_typing_target(" ").unwrap_err(), Error::NothingToType);
+        assert_eq!(
+            parse_typing_target_with_options("［＃注］", &aozora).unwrap_err(),
+            Error::NothingToType
+        );
+    }
+
+    #[test]
+    fn spaces_separate_words() {
+        let target = parse_typing_target(" ねこ　と いぬ ").unwrap();
+
+        assert_eq!(
+            target.displayed_chunks,
+            ["ね", "こ", "　", "と", " ", "い", "ぬ"]
+        );
+        assert_eq!(target.words, [0..2, 3..4, 5..7]);
+        assert_eq!(spellings("ね こ", 1, 0), [" ", ""]);
+
+        let required = ParseOptions {
+            spaces: Spaces::Required,
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options("ね こ", &required).unwrap();
+        assert_eq!(target.typed_chunks[1][0].typed, [" "]);
+    }
+
+    #[test]
+    fn foreign_sounds_are_single_chunks_in_either_script() {
+        for line in ["ファイル", "ふぁいる"] {
+            let target = parse_typing_target(line).unwrap();
+            assert_eq!(target.typed_chunks.len(), 3);
+            assert_eq!(target.hint(0).unwrap(), "fa");
+        }
+
+        assert_eq!(spellings("ティー", 0, 0)[0], "thi");
+        assert_eq!(spellings("でぃすく", 0, 0)[0], "dhi");
+        assert_eq!(spellings("ゔぁ", 0, 0)[0], "va");
+        assert_eq!(spellings("しぇ", 0, 0)[0], "she");
+        assert!(spellings("うぉ", 0, 0).contains(&"who".to_owned()));
+        assert!(spellings("ファ", 0, 0).contains(&"fuxa".to_owned()));
+    }
+
+    #[test]
+    fn small_kana_stand_alone_when_they_do_not_combine() {
+        let target = parse_typing_target("ぁあゃアァ").unwrap();
+        assert_eq!(target.displayed_chunks, ["ぁ", "あ", "ゃ", "ア", "ァ"]);
+        assert_eq!(spellings("ぁ", 0, 0), ["xa", "la"]);
+        assert_eq!(spellings("あゃ", 1, 0), ["xya", "lya"]);
+
+        assert_eq!(spellings("ヵ", 0, 0), ["xka", "lka"]);
+        assert_eq!(spellings("ヶ", 0, 0), ["xke", "lke"]);
+        assert_eq!(spellings("ゎ", 0, 0), ["xwa", "lwa"]);
+    }
+
+    #[test]
+    fn di_combines_with_small_ya_yu_yo() {
+        assert_eq!(
+            parse_typing_target("ぢゃあね").unwrap().displayed_chunks[0],
+            "ぢゃ"
+        );
+        assert_eq!(spellings("ぢゃ", 0, 0)[0], "dya");
+        assert_eq!(spellings("ヂョ", 0, 0)[0], "dyo");
+        assert!(spellings("ぢゅ", 0, 0).contains(&"dixyu".to_owned()));
+    }
+
+    #[test]
+    fn only_kanji_right_before_the_reading_are_displayed_with_it() {
+        let target = parse_typing_target("ノ内(うち)").unwrap();
+        assert_eq!(target.displayed_chunks, ["ノ", "内"]);
+        assert_eq!(target.hint(0).unwrap(), "no");
+        assert_eq!(target.hint(1).unwrap(), "uchi");
+
+        let target = parse_typing_target("|山ノ内(やまのうち)町(まち)").unwrap();
+        assert_eq!(target.displayed_chunks, ["山ノ内", "町"]);
+        assert_eq!(target.hint(0).unwrap(), "yamanouchi");
+    }
+
+    #[test]
+    fn digits_need_a_marker_to_share_a_reading_with_kanji() {
+        let target = parse_typing_target("2023年(ねん)").unwrap();
+        assert_eq!(target.displayed_chunks, ["2", "0", "2", "3", "年"]);
+        assert_eq!(target.hint(4).unwrap(), "nenn");
+
+        let target = parse_typing_target("3月(がつ)").unwrap();
+        assert_eq!(target.displayed_chunks, ["3", "月"]);
+
+        let target = parse_typing_target("11(じゅういち)月(がつ)|1日(ついたち)").unwrap();
+        assert_eq!(target.displayed_chunks, ["11", "月", "1日"]);
+        assert_eq!(target.hint(2).unwrap(), "tsuitachi");
+    }
+
+    #[test]
+    fn kanji_without_a_reading_are_reported_where_they_start() {
+        assert_eq!(
+            parse_typing_target("ねこ猫です").unwrap_err(),
+            Error::MissingReading {
+                kanji: "猫".to_owned(),
+                char_offset: 2,
+                byte_offset: 6,
+            }
+        );
+        assert_eq!(
+            parse_html_typing_target("漢字").unwrap_err(),
+            Error::MissingReading {
+                kanji: "漢字".to_owned(),
+                char_offset: 0,
+                byte_offset: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn long_vowels_are_typed_as_a_hyphen() {
+        assert_eq!(spellings("らーめん", 1, 0), ["-"]);
+        assert_eq!(spellings("コーヒー", 3, 0), ["-"]);
+    }
+
+    #[test]
+    fn long_vowels_fold_into_the_kana_before_them() {
+        let options = ParseOptions {
+            fold_long_vowels: true,
+            ..Default::default()
+        };
+        let parse = |line| {
+            parse_typing_target_with_options(line, &options)
+                .unwrap()
+                .displayed_chunks
+        };
+
+        assert_eq!(parse("コーヒー"), ["コー", "ヒー"]);
+        assert_eq!(
+            parse_typing_target_with_options("コーヒー", &options)
+                .unwrap()
+                .hint(0)
+                .unwrap(),
+            "ko-"
+        );
+        assert_eq!(parse("あ ー"), ["あ", " ", "ー"]);
+        assert_eq!(parse("、ー"), ["、", "ー"]);
+
+        let target = parse_typing_target_with_options("あ ーい", &options).unwrap();
+        assert_eq!(target.words, [0..1, 2..4]);
+    }
+
+    #[test]
+    fn reports_where_an_unknown_kana_is() {
+        assert_eq!(
+            parse_typing_target("ねこあ\u{3099}").unwrap_err(),
+            Error::UnknownKana {
+                kana: "あ\u{3099}".to_owned(),
+                char_offset: 2,
+                byte_offset: 6,
+            }
+        );
+        assert_eq!(
+            parse_typing_target("猫(ねこ)ｱﾞ").unwrap_err(),
+            Error::UnknownKana {
+                kana: "ｱﾞ".to_owned(),
+                char_offset: 5,
+                byte_offset: 11,
+            }
+        );
+        assert_eq!(
+            parse_typing_target("ねこ@").unwrap_err(),
+            Error::Unexpected {
+                found: Some('@'),
+                char_offset: 2,
+                byte_offset: 6,
+            }
+        );
+    }
+
+    #[test]
+    fn reads_aozora_ruby_and_skips_annotations() {
+        let options = ParseOptions {
+            ruby_syntax: RubySyntax::Aozora,
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options(
+            "｜山ノ内《やまのうち》［＃「山ノ内」に傍点］町《まち》",
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(target.displayed_chunks, ["山ノ内", "町"]);
+        assert_eq!(target.hint(0).unwrap(), "yamanouchi");
+        assert_eq!(
+            parse_typing_target_with_options(
+                "猫《ねこ》です［＃「です」は底本では「でした」］",
+                &options
+            )
+            .unwrap()
+            .displayed_chunks,
+            ["猫", "で", "す"]
+        );
+        assert!(parse_typing_target_with_options("猫(ねこ)", &options).is_err());
+    }
+
+    #[test]
+    fn punctuation_is_typed_as_its_key() {
+        let target = parse_typing_target("「ほんとう？」、うん。").unwrap();
+
+        assert_eq!(target.displayed_chunks[0], "「");
+        assert_eq!(target.hint(0).unwrap(), "[");
+        assert_eq!(spellings("「ほんとう？」、うん。", 5, 0), ["?"]);
+        assert_eq!(spellings("「ほんとう？」、うん。", 6, 0), ["]"]);
+        assert_eq!(spellings("「ほんとう？」、うん。", 7, 0), [","]);
+        assert_eq!(spellings("「ほんとう？」、うん。", 10, 0), ["."]);
+    }
+
+    #[test]
+    fn latin_letters_and_digits_are_typed_in_ascii() {
+        let target = parse_typing_target("CDを買(か)う").unwrap();
+
+        assert_eq!(target.displayed_chunks, ["C", "D", "を", "買", "う"]);
+        assert_eq!(spellings("CDを買(か)う", 0, 0), ["C", "c"]);
+        assert_eq!(spellings("１２３", 0, 0), ["1"]);
+
+        let options = ParseOptions {
+            case_sensitive: true,
+            require_ascii_digits: false,
+            ..Default::default()
+        };
+        let target = parse_typing_target_with_options("ＷｉＦｉ１", &options).unwrap();
+        assert_eq!(target.displayed_chunks, ["Ｗ", "ｉ", "Ｆ", "ｉ", "１"]);
+        assert_eq!(target.typed_chunks[0][0].typed, ["W"]);
+        assert_eq!(target.typed_chunks[4][0].typed, ["1", "１"]);
+    }
+
+    #[test]
+    fn half_width_katakana_keep_their_text_unless_normalized() {
+        let target = parse_typing_target("ｶﾞｯｺｳ｡").unwrap();
+
+        assert_eq!(target.displayed_chunks, ["ｶﾞ", "ｯ", "ｺ", "ｳ", "｡"]);
+        assert_eq!(target.typed_chunks[0][0].kana, "ガ");
+        assert_eq!(target.hint(0).unwrap(), "ga");
+        assert_eq!(target.hint(4).unwrap(), ".");
+
+        let options = ParseOptions {
+            normalize_half_width: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_typing_target_with_options("ｶﾞｯｺｳ｡", &options)
+                .unwrap()
+                .displayed_chunks,
+            ["ガ", "ッ", "コ", "ウ", "。"]
+        );
+    }
+
+    #[test]
+    fn decomposed_kana_are_displayed_composed() {
+        let target = parse_typing_target("か\u{3099}っこう").unwrap();
+
+        assert_eq!(target.displayed_chunks, ["が", "っ", "こ", "う"]);
+        assert_eq!(target.hint(0).unwrap(), "ga");
+        assert_eq!(
+            parse_typing_target("ハ\u{309a}ーティー")
+                .unwrap()
+                .displayed_chunks,
+            ["パ", "ー", "ティ", "ー"]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Parsing whole passages, a line at a time, for typing paragraph by
+//! paragraph.
+
+use std::fmt;
+
+use crate::{parse_typing_target_with_options, Error, ParseOptions, TypingTarget};
+
+/// A line of a passage, parsed into a target.
+#[derive(Clone, Debug)]
+pub struct PassageLine {
+    pub target: TypingTarget,
+    /// The index of the line in the passage text, counting blank lines.
+    pub line: usize,
+    /// The index of the paragraph the line is in.
+    pub paragraph: usize,
+}
+
+/// A line of a passage that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassageError {
+    /// The index of the line in the passage text, counting blank lines.
+    pub line: usize,
+    pub error: Error,
+}
+
+impl fmt::Display for PassageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line + 1, self.error)
+    }
+}
+
+impl std::error::Error for PassageError {}
+
+/// Parses every line of `passage` with [`parse_typing_target`], in order.
+///
+/// Paragraphs are separated by one or more blank lines, which are not parsed.
+/// If any line fails, the errors of all the lines that failed are returned.
+///
+/// [`parse_typing_target`]: crate::parse_typing_target
+pub fn parse_passage(passage: &str) -> Result<Vec<PassageLine>, Vec<PassageError>> {
+    parse_passage_with_options(passage, &ParseOptions::default())
+}
+
+/// Like [`parse_passage`], with settings other than the defaults.
+pub fn parse_passage_with_options(
+    passage: &str,
+    options: &ParseOptions,
+) -> Result<Vec<PassageLine>, Vec<PassageError>> {
+    let mut lines = vec![];
+    let mut errors = vec![];
+
+    let mut paragraph = 0;
+    let mut in_paragraph = false;
+
+    for (line, text) in passage.lines().enumerate() {
+        if text.trim().is_empty() {
+            if in_paragraph {
+                paragraph += 1;
+                in_paragraph = false;
+            }
+            continue;
+        }
+        in_paragraph = true;
+
+        match parse_typing_target_with_options(text, options) {
+            Ok(target) => lines.push(PassageLine {
+                target,
+                line,
+                paragraph,
+            }),
+            Err(error) => errors.push(PassageError { line, error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(lines)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_lines_and_paragraphs() {
+        let passage =
+            "\n吾輩(わがはい)は猫(ねこ)である。\n名前(なまえ)はまだ無(な)い。\n\n\nどこで生(う)まれたか\n";
+        let lines: Vec<(usize, usize)> = parse_passage(passage)
+            .unwrap()
+            .iter()
+            .map(|line| (line.line, line.paragraph))
+            .collect();
+
+        assert_eq!(lines, [(1, 0), (2, 0), (5, 1)]);
+    }
+
+    #[test]
+    fn reports_every_line_that_fails() {
+        let errors = parse_passage("ねこ\n猫\nいぬ\nあ\u{3099}").unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+
+        assert_eq!(lines, [1, 3]);
+        assert!(matches!(errors[0].error, Error::MissingReading { .. }));
+        assert_eq!(
+            errors[0].to_string(),
+            "line 2: no reading for `猫` at character 0"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Turning romaji back into kana, with the spellings of
+//! [`kana_to_typed_chunks`].
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::kana::{
+    kana_to_typed_chunks, to_hiragana, to_katakana, CHOONPU, HIRAGANA, KATAKANA, PUNCTUATION,
+    SOKUON, SUTEGANA,
+};
+use crate::Error;
+
+/// The kana that romaji is turned into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Script {
+    #[default]
+    Hiragana,
+    Katakana,
+}
+
+/// Kana that IMEs never type from romaji, and that share their first spelling
+/// with kana they do type, like "va" for ヷ and ヴァ.
+static NOT_TYPED: &[&str] = &["ヷ", "ヸ", "ヹ", "ヺ", "クヮ", "グヮ", "くゎ", "ぐゎ", "～"];
+
+/// Every spelling, with the hiragana it may stand for and how far down that
+/// kana's list of spellings it comes.
+fn spellings() -> &'static HashMap<&'static str, Vec<(String, usize)>> {
+    static SPELLINGS: OnceLock<HashMap<&'static str, Vec<(String, usize)>>> = OnceLock::new();
+
+    SPELLINGS.get_or_init(|| {
+        let mut kana: Vec<String> = vec![];
+
+        for base in HIRAGANA.chars().chain(KATAKANA.chars()) {
+            kana.push(base.into());
+            for sutegana in SUTEGANA.chars() {
+                kana.push([base, sutegana].iter().collect());
+            }
+        }
+        kana.extend(
+            SUTEGANA
+                .chars()
+                .chain(SOKUON.chars())
+                .chain(CHOONPU.chars())
+                .chain(PUNCTUATION.chars())
+                .map(String::from),
+        );
+
+        let mut spellings: HashMap<&'static str, Vec<(String, usize)>> = HashMap::new();
+
+        for kana in kana {
+            if NOT_TYPED.contains(&kana.as_str()) {
+                continue;
+            }
+
+            let typed = match kana_to_typed_chunks(&kana) {
+                Some(typed) => typed,
+                None => continue,
+            };
+            let hiragana: String = kana.chars().map(to_hiragana).collect();
+
+            for (rank, spelling) in typed.iter().enumerate() {
+                let candidates = spellings.entry(*spelling).or_default();
+                if !candidates.iter().any(|(k, _)| *k == hiragana) {
+                    candidates.push((hiragana.clone(), rank));
+                }
+            }
+        }
+
+        spellings
+    })
+}
+
+/// Turns `romaji` into kana, the way an IME would.
+///
+/// A doubled consonant, as in "kitte", or a "t" before "ch" becomes a sokuon.
+/// A single "n" becomes ん before a consonant, and "nn", "n'" and "xn" always
+/// do, as they do for a [`Matcher`](crate::Matcher), so こんな is "konnna".
+/// Unlike the matcher, which waits for "nn" at the end of a line, a final "n"
+/// becomes ん too, as in "hon". Where a spelling is given for more than one
+/// kana, the kana it is the first spelling of wins, so "ji" becomes じ rather
+/// than ぢ. Letters may be in either case, and anything that is not romaji,
+/// like kana or spaces, is kept as it is.
+///
+/// An "n" that could either end ん or start the next kana is reported as
+/// [`AmbiguousRomaji`](Error::AmbiguousRomaji): the "nna" of "konna", which an
+/// IME reads as こんあ but looks like こんな, and the "nya" of "kanya", which
+/// may be かにゃ or かんや. Writing "n'" for ん, as in "kon'na", settles it.
+pub fn romaji_to_kana(romaji: &str, script: Script) -> Result<String, Error> {
+    let chars: Vec<char> = romaji.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let spellings = spellings();
+    let longest = spellings.keys().map(|s| s.len()).max().unwrap_or(0);
+
+    let mut kana = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let error_at = |i: usize| {
+            let end = (i..chars.len())
+                .find(|&j| "aiueo".contains(chars[j]))
+                .map_or(chars.len(), |j| j + 1);
+
+            (
+                romaji.chars().skip(i).take(end - i).collect::<String>(),
+                i,
+                romaji.chars().take(i).map(char::len_utf8).sum::<usize>(),
+            )
+        };
+
+        if let Some(readings) = n_readings(&chars, i) {
+            let (romaji, char_offset, byte_offset) = error_at(i);
+            return Err(Error::AmbiguousRomaji {
+                romaji,
+                kana: readings
+                    .into_iter()
+                    .map(|reading| in_script(reading, script))
+                    .collect(),
+                char_offset,
+                byte_offset,
+            });
+        }
+
+        if c == 'n' && !next.is_some_and(|n| "aiueoy".contains(n)) {
+            i += match next {
+                Some('\'') | Some('n') => 2,
+                _ => 1,
+            };
+            kana.push('ん');
+            continue;
+        }
+
+        let doubled = c.is_ascii_lowercase()
+            && !"aiueonxl".contains(c)
+            && (next == Some(c)
+                || (c == 't' && next == Some('c') && chars.get(i + 2) == Some(&'h')));
+        if doubled {
+            kana.push('っ');
+            i += 1;
+            continue;
+        }
+
+        let matched = (1..=longest.min(chars.len() - i)).rev().find_map(|len| {
+            let spelling: String = chars[i..i + len].iter().collect();
+            spellings
+                .get(spelling.as_str())
+                .map(|candidates| (len, candidates))
+        });
+
+        let (len, candidates) = match matched {
+            Some(matched) => matched,
+            None if c.is_ascii_alphabetic() || c == '\'' => {
+                let (romaji, char_offset, byte_offset) = error_at(i);
+                return Err(Error::UnknownRomaji {
+                    romaji,
+                    char_offset,
+                    byte_offset,
+                });
+            }
+            None => {
+                kana.push(romaji.chars().nth(i).unwrap_or(c));
+                i += 1;
+                continue;
+            }
+        };
+
+        if let Some((best, _)) = candidates.iter().min_by_key(|(_, rank)| *rank) {
+            kana.push_str(best);
+        }
+        i += len;
+    }
+
+    Ok(in_script(kana, script))
+}
+
+/// Both ways of reading an "n" at `i` that could end ん or start the next
+/// kana, the way an IME reads it first, or `None` if there is only one.
+fn n_readings(chars: &[char], i: usize) -> Option<Vec<String>> {
+    let is_vowel = |c: char| "aiueo".contains(c);
+
+    // where the kana after ん would start, and where the n-kana would
+    let (after_n, n_kana) = match chars.get(i..i + 2)? {
+        ['n', 'n'] if chars.get(i + 2).is_some_and(|&c| is_vowel(c) || c == 'y') => (i + 2, i + 1),
+        ['n', 'y'] if i > 0 && is_vowel(chars[i - 1]) => (i + 1, i),
+        _ => return None,
+    };
+    let end = (after_n..chars.len()).find(|&j| is_vowel(chars[j]))? + 1;
+
+    let read = |from: usize| {
+        let romaji: String = chars[from..end].iter().collect();
+        romaji_to_kana(&romaji, Script::Hiragana).ok()
+    };
+    let n = format!("ん{}", read(after_n)?);
+    let n_kana = read(n_kana)?;
+
+    Some(if after_n == i + 2 {
+        vec![n, format!("ん{}", n_kana)]
+    } else {
+        vec![n_kana, n]
+    })
+}
+
+fn in_script(hiragana: String, script: Script) -> String {
+    match script {
+        Script::Hiragana => hiragana,
+        Script::Katakana => hiragana.chars().map(to_katakana).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hiragana(romaji: &str) -> String {
+        romaji_to_kana(romaji, Script::Hiragana).unwrap()
+    }
+
+    #[test]
+    fn turns_romaji_into_kana() {
+        assert_eq!(hiragana("sushi"), "すし");
+        assert_eq!(hiragana("Fairu"), "ふぁいる");
+        assert_eq!(hiragana("ji"), "じ");
+        assert_eq!(hiragana("wi-"), "うぃー");
+        assert_eq!(hiragana("thi"), "てぃ");
+        assert_eq!(
+            romaji_to_kana("kyouto", Script::Katakana).unwrap(),
+            "キョウト"
+        );
+    }
+
+    #[test]
+    fn reads_n_like_the_matcher() {
+        assert_eq!(hiragana("kanji"), "かんじ");
+        assert_eq!(hiragana("konnnichiha"), "こんにちは");
+        assert_eq!(hiragana("kon'nichiha"), "こんにちは");
+        assert_eq!(hiragana("kon'ya"), "こんや");
+        assert_eq!(hiragana("nyanko"), "にゃんこ");
+    }
+
+    #[test]
+    fn reads_a_final_n_as_n() {
+        assert_eq!(hiragana("hon"), "ほん");
+        assert_eq!(hiragana("shinbun"), "しんぶん");
+        assert_eq!(hiragana("hon desu"), "ほん です");
+    }
+
+    #[test]
+    fn doubles_consonants_into_sokuon() {
+        assert_eq!(hiragana("kitte"), "きって");
+        assert_eq!(hiragana("macchi"), "まっち");
+        assert_eq!(hiragana("matcha"), "まっちゃ");
+        assert_eq!(hiragana("xtu"), "っ");
+    }
+
+    #[test]
+    fn reports_romaji_without_kana() {
+        assert_eq!(
+            romaji_to_kana("あkqa", Script::Hiragana),
+            Err(Error::UnknownRomaji {
+                romaji: "kqa".to_owned(),
+                char_offset: 1,
+                byte_offset: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn reports_n_that_may_be_read_two_ways() {
+        assert_eq!(
+            romaji_to_kana("konna", Script::Hiragana),
+            Err(Error::AmbiguousRomaji {
+                romaji: "nna".to_owned(),
+                kana: vec!["んあ".to_owned(), "んな".to_owned()],
+                char_offset: 2,
+                byte_offset: 2,
+            })
+        );
+        assert_eq!(
+            romaji_to_kana("kanya", Script::Katakana),
+            Err(Error::AmbiguousRomaji {
+                romaji: "nya".to_owned(),
+                kana: vec!["ニャ".to_owned(), "ンヤ".to_owned()],
+                char_offset: 2,
+                byte_offset: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Loading word lists, with the settings of each entry.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+use crate::{parse_typing_target_with_options, Error, ParseOptions, TypingTarget};
+
+/// The ways a word list can be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordListFormat {
+    /// One entry per line, with any attributes in square brackets at the end:
+    ///
+    /// ```text
+    /// # comments and blank lines are skipped
+    /// 山(やま)のぼり [fixed, weight=2, tag=sports, tag=outdoors]
+    /// ねこ [disabled]
+    /// いぬ
+    /// ```
+    Text,
+    /// A `words` array of tables with a `text` and, optionally, `fixed`,
+    /// `disabled`, `weight` and `tags`. Needs the `toml` feature.
+    ///
+    /// ```toml
+    /// [[words]]
+    /// text = "山(やま)のぼり"
+    /// fixed = true
+    /// weight = 2.0
+    /// tags = ["sports", "outdoors"]
+    /// ```
+    #[cfg(feature = "toml")]
+    Toml,
+    /// The same as [`Toml`](WordListFormat::Toml), as a JSON object like
+    /// `{"words": [{"text": "ねこ", "disabled": true}]}`. Needs the `json`
+    /// feature.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+/// An entry of a word list that could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryError {
+    /// The index of the line in a [`Text`](WordListFormat::Text) list,
+    /// counting blank lines, or of the entry in the `words` array otherwise.
+    pub entry: usize,
+    pub error: Error,
+}
+
+impl fmt::Display for EntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entry {}: {}", self.entry + 1, self.error)
+    }
+}
+
+impl std::error::Error for EntryError {}
+
+/// Why a word list could not be loaded.
+#[derive(Debug)]
+pub enum WordListError {
+    Io(io::Error),
+    /// A TOML or JSON file that is not shaped like a word list.
+    Format(String),
+    /// Every entry that could not be parsed.
+    Entries(Vec<EntryError>),
+}
+
+impl fmt::Display for WordListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WordListError::Io(error) => write!(f, "{}", error),
+            WordListError::Format(message) => write!(f, "{}", message),
+            WordListError::Entries(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", errors.join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for WordListError {}
+
+impl From<io::Error> for WordListError {
+    fn from(error: io::Error) -> Self {
+        WordListError::Io(error)
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[derive(Deserialize)]
+struct WordList {
+    words: Vec<Entry>,
+}
+
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+struct Entry {
+    text: String,
+    fixed: bool,
+    disabled: bool,
+    weight: f32,
+    tags: Vec<String>,
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            fixed: false,
+            disabled: false,
+            weight: 1.0,
+            tags: vec![],
+        }
+    }
+}
+
+impl Entry {
+    /// Reads a line like `ねこ [disabled, tag=animals]`.
+    fn from_line(line: &str) -> Result<Self, Error> {
+        let (text, attributes) = match line.trim_end().strip_suffix(']') {
+            Some(rest) if rest.contains('[') => {
+                let open = rest.rfind('[').unwrap_or_default();
+                (&rest[..open], rest[open + 1..].split(','))
+            }
+            _ => (line, "".split(',')),
+        };
+
+        let mut entry = Entry {
+            text: text.trim().to_owned(),
+            ..Default::default()
+        };
+
+        for attribute in attributes.map(str::trim).filter(|a| !a.is_empty()) {
+            let invalid = || Error::InvalidAttribute {
+                attribute: attribute.to_owned(),
+            };
+
+            match attribute.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                None if attribute == "fixed" => entry.fixed = true,
+                None if attribute == "disabled" => entry.disabled = true,
+                Some(("weight", weight)) => entry.weight = weight.parse().map_err(|_| invalid())?,
+                Some(("tag", tag)) if !tag.is_empty() => entry.tags.push(tag.to_owned()),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(entry)
+    }
+
+    fn into_target(self, options: &ParseOptions) -> Result<TypingTarget, Error> {
+        // weights are used to pick entries at random
+        if !self.weight.is_finite() || self.weight < 0.0 {
+            return Err(Error::InvalidAttribute {
+                attribute: format!("weight={}", self.weight),
+            });
+        }
+
+        Ok(TypingTarget {
+            fixed: self.fixed,
+            disabled: self.disabled,
+            weight: self.weight,
+            tags: self.tags,
+            ..parse_typing_target_with_options(&self.text, options)?
+        })
+    }
+}
+
+/// Parses every entry of a word list. If any entry fails, the errors of all
+/// the entries that failed are returned. Weights must be finite and not
+/// negative.
+pub fn parse_word_list(
+    text: &str,
+    format: WordListFormat,
+    options: &ParseOptions,
+) -> Result<Vec<TypingTarget>, WordListError> {
+    let entries: Vec<(usize, Result<Entry, Error>)> = match format {
+        WordListFormat::Text => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|(i, line)| (i, Entry::from_line(line)))
+            .collect(),
+        #[cfg(feature = "toml")]
+        WordListFormat::Toml => toml::from_str::<WordList>(text)
+            .map_err(|e| WordListError::Format(e.to_string()))?
+            .words
+            .into_iter()
+            .map(Ok)
+            .enumerate()
+            .collect(),
+        #[cfg(feature = "json")]
+        WordListFormat::Json => serde_json::from_str::<WordList>(text)
+            .map_err(|e| WordListError::Format(e.to_string()))?
+            .words
+            .into_iter()
+            .map(Ok)
+            .enumerate()
+            .collect(),
+    };
+
+    let mut targets = vec![];
+    let mut errors = vec![];
+
+    for (entry, result) in entries {
+        match result.and_then(|e| e.into_target(options)) {
+            Ok(target) => targets.push(target),
+            Err(error) => errors.push(EntryError { entry, error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(targets)
+    } else {
+        Err(WordListError::Entries(errors))
+    }
+}
+
+/// Like [`parse_word_list`], reading the list from a UTF-8 file.
+pub fn load_word_list(
+    path: impl AsRef<Path>,
+    format: WordListFormat,
+    options: &ParseOptions,
+) -> Result<Vec<TypingTarget>, WordListError> {
+    parse_word_list(&fs::read_to_string(path)?, format, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, format: WordListFormat) -> Result<Vec<TypingTarget>, WordListError> {
+        parse_word_list(text, format, &ParseOptions::default())
+    }
+
+    /// The indexes of the entries that failed.
+    fn failed(text: &str, format: WordListFormat) -> Vec<usize> {
+        match parse(text, format) {
+            Err(WordListError::Entries(errors)) => errors.iter().map(|e| e.entry).collect(),
+            other => panic!("expected entry errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_attributes_of_text_entries() {
+        let text =
+            "# animals\nねこ [fixed, weight=2, tag=animals, tag=pets]\n\nいぬ\nとり [disabled]";
+        let targets = parse(text, WordListFormat::Text).unwrap();
+
+        assert_eq!(targets.len(), 3);
+        assert!(targets[0].fixed);
+        assert_eq!(targets[0].weight, 2.0);
+        assert_eq!(targets[0].tags, ["animals", "pets"]);
+        assert_eq!(targets[1].displayed_chunks, ["い", "ぬ"]);
+        assert_eq!(targets[1].weight, 1.0);
+        assert!(targets[2].disabled);
+    }
+
+    #[test]
+    fn reports_every_entry_that_fails() {
+        let text = "ねこ\nとり [loud]\n\n猫\nいぬ [weight=x]";
+
+        assert_eq!(failed(text, WordListFormat::Text), [1, 3, 4]);
+    }
+
+    #[test]
+    fn rejects_negative_and_non_finite_weights() {
+        let text = "ねこ [weight=-3]\nいぬ [weight=NaN]\nとり [weight=inf]\nうし [weight=0]";
+
+        assert_eq!(failed(text, WordListFormat::Text), [0, 1, 2]);
+        assert_eq!(
+            Entry::from_line("ねこ [weight=-3]")
+                .and_then(|entry| entry.into_target(&ParseOptions::default()))
+                .unwrap_err(),
+            Error::InvalidAttribute {
+                attribute: "weight=-3".to_owned()
+            }
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn reads_toml() {
+        let text = "[[words]]\ntext = \"山(やま)のぼり\"\nfixed = true\ntags = [\"sports\"]\n\n[[words]]\ntext = \"ねこ\"\nweight = -1.0\n";
+
+        assert_eq!(failed(text, WordListFormat::Toml), [1]);
+        let targets = parse(&text[..text.find("\n\n").unwrap()], WordListFormat::Toml).unwrap();
+        assert!(targets[0].fixed);
+        assert_eq!(targets[0].tags, ["sports"]);
+        assert!(matches!(
+            parse("words = 1", WordListFormat::Toml),
+            Err(WordListError::Format(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn reads_json() {
+        let text = r#"{"words": [{"text": "ねこ", "disabled": true, "weight": 0.5}]}"#;
+        let targets = parse(text, WordListFormat::Json).unwrap();
+
+        assert!(targets[0].disabled);
+        assert_eq!(targets[0].weight, 0.5);
+        assert_eq!(
+            failed(
+                r#"{"words": [{"text": "ねこ", "weight": -1}]}"#,
+                WordListFormat::Json
+            ),
+            [0]
+        );
+    }
+}